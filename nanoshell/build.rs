@@ -13,11 +13,11 @@ fn main() -> () {
                 QS_ALLINPUT, PM_REMOVE, TRUE, FALSE, CS_HREDRAW, CS_VREDRAW,
                 WS_POPUP, WS_THICKFRAME, WS_OVERLAPPEDWINDOW, GWL_EXSTYLE, WS_SYSMENU, WS_DISABLED, WS_CAPTION,
                 WS_POPUPWINDOW, WS_CLIPCHILDREN, WS_MAXIMIZEBOX, WS_MINIMIZEBOX, WS_BORDER,
-                GWLP_USERDATA, SW_SHOW, SW_HIDE, GWL_STYLE,
-                IDC_ARROW, SIZE_RESTORED,
+                GWLP_USERDATA, SW_SHOW, SW_HIDE, SW_MINIMIZE, SW_MAXIMIZE, SW_RESTORE, GWL_STYLE,
+                IDC_ARROW, SIZE_RESTORED, WA_INACTIVE,
                 SWP_NOZORDER, SWP_NOSIZE, SWP_NOACTIVATE, SWP_NOMOVE, SWP_FRAMECHANGED,
                 WS_VISIBLE, HTNOWHERE, WS_EX_APPWINDOW, WS_DLGFRAME, WS_EX_NOACTIVATE, WS_EX_LAYOUTRTL, WS_EX_RTLREADING,
-                WS_EX_NOREDIRECTIONBITMAP,
+                WS_EX_NOREDIRECTIONBITMAP, WS_EX_TOOLWINDOW, WS_EX_LAYERED, LWA_ALPHA,
                 FLASHW_ALL, SC_CLOSE, MF_BYCOMMAND, MF_BYPOSITION, MF_GRAYED, MF_DISABLED, MF_POPUP, GWL_HWNDPARENT, MF_HILITE, MF_MOUSESELECT,
                 MIM_MENUDATA, MIM_STYLE, MNS_NOTIFYBYPOS, MIM_BACKGROUND,
                 MIIM_FTYPE, MIIM_ID, MIIM_STATE, MIIM_STRING, MIIM_SUBMENU,
@@ -38,7 +38,7 @@ fn main() -> () {
                 WM_MBUTTONUP, WM_XBUTTONUP,
                 WM_TIMER, WM_MENUCOMMAND, WM_COMMAND, WM_USER, WM_MOUSELEAVE, WM_CANCELMODE, WM_MENUSELECT,
                 WM_CHANGEUISTATE, WM_UPDATEUISTATE, WM_KEYDOWN, WM_KEYUP, WM_SYSKEYUP, WM_SETFOCUS, WM_DWMCOMPOSITIONCHANGED,
-                WM_NCLBUTTONDOWN, WM_ERASEBKGND, WM_ENTERSIZEMOVE, WM_EXITSIZEMOVE,
+                WM_NCLBUTTONDOWN, WM_ERASEBKGND, WM_ENTERSIZEMOVE, WM_EXITSIZEMOVE, WM_MOVE,
                 WM_QUERYUISTATE, WM_SYSCOMMAND,
                 TPM_LEFTALIGN, TPM_RIGHTALIGN, TPM_TOPALIGN, TPM_BOTTOMALIGN, TPM_HORIZONTAL, TPM_VERTICAL,
                 TPM_RETURNCMD,
@@ -48,6 +48,7 @@ fn main() -> () {
                 EnumDisplayMonitors, ClientToScreen, ScreenToClient, CreateSolidBrush, GetDC, ReleaseDC,
                 CreateDIBSection, DeleteObject, RedrawWindow, GetDCEx, ExcludeClipRect,
                 FillRect, PAINTSTRUCT, BeginPaint, EndPaint,
+                MonitorFromWindow, GetMonitorInfoW, MONITORINFO, MONITOR_FROM_FLAGS,
             },
             windows::win32::menus_and_resources::{
                 LoadCursorW, GetSystemMenu, EnableMenuItem, CreatePopupMenu, DestroyMenu, AppendMenuW,
@@ -71,9 +72,9 @@ fn main() -> () {
                 RegisterClassW, UnregisterClassW, PostMessageW, SendMessageW,
                 GetMessageW, PeekMessageW, TranslateMessage, DispatchMessageW, DestroyWindow, CreateWindowExW,
                 DefWindowProcW, SetWindowLongW, GetWindowLongW, ShowWindow, SetProcessDPIAware,
-                SetWindowPos, GetWindowRect, GetClientRect, SetParent, GetParent, MoveWindow, SetForegroundWindow,
+                SetWindowPos, SetWindowTextW, GetWindowRect, GetClientRect, SetParent, GetParent, MoveWindow, SetForegroundWindow,
                 SetTimer, SetWindowsHookExW, UnhookWindowsHookEx, CallNextHookEx, FindWindowW,
-                GetGUIThreadInfo, WindowFromPoint,
+                GetGUIThreadInfo, WindowFromPoint, IsIconic, IsZoomed, SetLayeredWindowAttributes,
                 // Structures
                 CREATESTRUCTW, MSG, WINDOWPOS, NCCALCSIZE_PARAMS
             },
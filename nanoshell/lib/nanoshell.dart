@@ -1,6 +1,9 @@
 library nanoshell;
 
 export 'src/accelerator.dart';
+export 'src/constants.dart' show ErrorCodes;
+export 'src/diagnostics.dart';
+export 'src/display.dart';
 export 'src/drag_drop.dart';
 export 'src/menu.dart';
 export 'src/menu_bar.dart';
@@ -1,9 +1,17 @@
+// Version of the window dispatcher protocol; Must match protocol::VERSION
+// in src/shell/constants.rs
+class Protocol {
+  static final version = 1;
+}
+
 class Channels {
   static final dispatcher = 'nanoshell/window.dispatcher';
   static final windowManager = '.window.window-manager';
   static final dropTarget = '.window.drop-target';
   static final dragSource = '.window.drag-source';
   static final menuManager = 'nanoshell/menu-manager';
+  static final diagnostics = 'nanoshell/diagnostics';
+  static final displayManager = 'nanoshell/display-manager';
 }
 
 class Events {
@@ -11,6 +19,10 @@ class Events {
   static final windowVisibilityChanged = 'event:window:visibility-changed';
   static final windowCloseRequest = 'event:window:close-request';
   static final windowClose = 'event:window:close';
+  static final windowStateChanged = 'event:window:state-changed';
+  static final windowActivationChanged = 'event:window:activation-changed';
+  static final windowGeometryChanged = 'event:window:geometry-changed';
+  static final displaysChanged = 'event:displays-changed';
 }
 
 class Methods {
@@ -29,6 +41,10 @@ class Methods {
   static final windowSupportedGeometry = 'method:window:supported-geometry';
 
   static final windowSetStyle = 'method:window:set-style';
+  static final windowSetTitle = 'method:window:set-title';
+  static final windowSetState = 'method:window:set-state';
+  static final windowGetState = 'method:window:get-state';
+  static final windowActivate = 'method:window:activate';
   static final windowPerformDrag = 'method:window:perform-window-drag';
 
   static final windowShowPopupMenu = 'method:window:show-popup-menu';
@@ -54,6 +70,12 @@ class Methods {
   static final menuOnAction = 'method:menu:on-action';
   static final menuSetAppMenu = 'method:menu:set-app-menu';
 
+  // Diagnostics
+  static final diagnosticsGet = 'method:diagnostics:get';
+
+  // Displays
+  static final displaysGet = 'method:displays:get';
+
   // Menubar
   static final menubarMoveToPreviousMenu =
       'method:menubar:move-to-previous-menu';
@@ -64,3 +86,43 @@ class Keys {
   static final dragDataFiles = 'drag-data:internal:files';
   static final dragDataURLs = 'drag-data:internal:urls';
 }
+
+// Stable error codes of PlatformException thrown for failed nanoshell calls;
+// Must be kept in sync with error_code module in src/error.rs
+class ErrorCodes {
+  // details: {'engineHandle': int}
+  static final invalidEngineHandle = 'invalid-engine-handle';
+  // details: {'windowHandle': int}
+  static final invalidWindowHandle = 'invalid-window-handle';
+  // details: {'menuHandle': int}
+  static final invalidMenuHandle = 'invalid-menu-handle';
+  static final engineShutDown = 'engine-shut-down';
+  static final timeout = 'timeout';
+  static final invalidReply = 'invalid-reply';
+  // details: {'windowHandle': int}
+  static final outboundQueueFull = 'outbound-queue-full';
+  // details: offending request value
+  static final invalidRequest = 'invalid-request';
+  // details: {'nativeVersion': int, 'dartVersion': int?}
+  static final incompatibleProtocol = 'incompatible-protocol';
+  static final noWindow = 'no-window';
+  // Reported as MissingPluginException instead of PlatformException
+  static final notImplemented = 'not-implemented';
+
+  // value errors - details: {'path': String} if error is in nested field
+  static final valueError = 'value-error';
+  static final valueConversionError = 'value-conversion-error';
+  static final valueWrongType = 'value-wrong-type';
+  static final valueNotAList = 'value-not-a-list';
+  static final valueNotAMap = 'value-not-a-map';
+
+  static final platformUnknownError = 'platform-unknown-error';
+  static final platformNotImplemented = 'platform-not-implemented';
+  static final platformNotAvailable = 'platform-not-available';
+  static final platformLaunchEngineFailure = 'platform-launch-engine-failure';
+  // details: {'channel': String}
+  static final platformSendMessageFailure = 'platform-send-message-failure';
+  static final platformNoEventFound = 'platform-no-event-found';
+  // details: {'hresult': int}
+  static final platformHResult = 'platform-hresult';
+}
@@ -0,0 +1,16 @@
+import 'package:flutter/services.dart';
+
+import 'constants.dart';
+
+final _diagnosticsChannel = MethodChannel(Channels.diagnostics);
+
+class Diagnostics {
+  // Returns snapshot of native application state: engines, windows, registered
+  // channel handlers, menus and pending scheduled callbacks. The result has
+  // same structure as Diagnostics struct in src/shell/diagnostics.rs.
+  static Future<Map<String, dynamic>> get() async {
+    final res = await _diagnosticsChannel
+        .invokeMapMethod<String, dynamic>(Methods.diagnosticsGet);
+    return res!;
+  }
+}
@@ -0,0 +1,66 @@
+import 'dart:ui';
+
+import 'package:flutter/services.dart';
+
+import 'constants.dart';
+import 'event.dart';
+import 'struts.dart';
+
+final _displayManagerChannel = MethodChannel(Channels.displayManager);
+
+// All rects are in logical coordinates with top left origin at primary display
+class Display {
+  Display({
+    required this.id,
+    required this.frame,
+    required this.workArea,
+    required this.scale,
+  });
+
+  final int id;
+  final Rect frame;
+  final Rect workArea;
+  final double scale;
+
+  static Display deserialize(dynamic value) {
+    final map = value as Map;
+    return Display(
+      id: map['id'],
+      frame: RectExt.deserialize(map['frame']),
+      workArea: RectExt.deserialize(map['workArea']),
+      scale: map['scale'],
+    );
+  }
+
+  @override
+  String toString() =>
+      'Display($id, $frame, workArea: $workArea, scale: $scale)';
+}
+
+class DisplayManager {
+  static DisplayManager instance() => _instance;
+
+  static final _instance = DisplayManager();
+
+  DisplayManager() {
+    _displayManagerChannel.setMethodCallHandler(_onMethodCall);
+  }
+
+  Future<List<Display>> getDisplays() async {
+    final res = await _displayManagerChannel
+        .invokeListMethod<dynamic>(Methods.displaysGet);
+    return _deserialize(res!);
+  }
+
+  // Fired when displays were added, removed or reconfigured
+  final displaysChangedEvent = Event<List<Display>>();
+
+  Future<dynamic> _onMethodCall(MethodCall call) async {
+    if (call.method == Events.displaysChanged) {
+      displaysChangedEvent.fire(_deserialize(call.arguments));
+    }
+  }
+
+  static List<Display> _deserialize(dynamic value) =>
+      (value as List).map(Display.deserialize).toList();
+}
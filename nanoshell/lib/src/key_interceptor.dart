@@ -1,5 +1,6 @@
 import 'dart:async';
 import 'dart:convert';
+import 'dart:ui' as ui;
 
 import 'package:flutter/cupertino.dart';
 import 'package:flutter/services.dart';
@@ -44,7 +45,8 @@ enum InterceptorStage {
 class KeyInterceptor {
   KeyInterceptor._() {
     WidgetsFlutterBinding.ensureInitialized();
-    _channel.setMessageHandler(_onMessage);
+    _previousHandler = ui.window.onPlatformMessage;
+    ui.window.onPlatformMessage = _onPlatformMessage;
   }
 
   void registerHandler(
@@ -74,12 +76,42 @@ class KeyInterceptor {
   final _handlersPre = <KeyInterceptorHandler>[];
   final _handlersPost = <KeyInterceptorHandler>[];
 
+  ui.PlatformMessageCallback? _previousHandler;
+
+  // Passes message to Flutter; On newer Flutter versions there is no previous
+  // handler and messages are delivered through channel buffers
+  void _forward(String channel, ByteData? message,
+      ui.PlatformMessageResponseCallback? callback) {
+    final previousHandler = _previousHandler;
+    if (previousHandler != null) {
+      previousHandler(channel, message, callback);
+    } else {
+      ui.channelBuffers.push(channel, message, callback ?? (_) {});
+    }
+  }
+
+  void _onPlatformMessage(String channel, ByteData? message,
+      ui.PlatformMessageResponseCallback? callback) {
+    if (channel == 'flutter/keyevent') {
+      unawaited(_onKeyEvent(message).then((data) => callback?.call(data)));
+    } else {
+      _forward(channel, message, callback);
+    }
+  }
+
   static ByteData _dataForHandled(bool handled) {
     final res = <String, dynamic>{'handled': handled};
     return StringCodec().encodeMessage(json.encode(res))!;
   }
 
-  Future<ByteData> _onMessage(ByteData? message) async {
+  Future<ByteData> _onKeyEvent(ByteData? message) async {
+    // Native side adds platform specific fields to the event; Key event can
+    // also be swallowed by message middleware, in which case reply is empty
+    message = await _channel.send(message);
+    if (message == null || message.lengthInBytes == 0) {
+      return _dataForHandled(true);
+    }
+
     final keyMessage = json.decode(StringCodec().decodeMessage(message) ?? '');
 
     final event = _keyEventFromMessage(keyMessage);
@@ -91,13 +123,12 @@ class KeyInterceptor {
     }
 
     final completer = Completer<ByteData>();
-    unawaited(WidgetsBinding.instance?.defaultBinaryMessenger
-        .handlePlatformMessage('flutter/keyevent', message, (data) {
+    _forward('flutter/keyevent', message, (data) {
       // macos with FN pressed seems to return null?
       data ??= _dataForHandled(false);
 
       completer.complete(data);
-    }));
+    });
     final data = await completer.future;
     final response = json.decode(StringCodec().decodeMessage(data) ?? '');
     if (response['handled'] == false) {
@@ -160,6 +160,55 @@ enum WindowFrame {
   noFrame,
 }
 
+enum WindowState {
+  normal,
+  minimized,
+  maximized,
+  fullScreen,
+}
+
+enum WindowPlacementPolicy {
+  // Center over parent window; Windows without parent are centered on display
+  centerOnParent,
+  // Center in work area of display with parent window (or the window itself)
+  centerOnDisplay,
+  // Offset from the most recently created window
+  cascade,
+  // Below anchor rect (or above if there is not enough space), i.e. for popups
+  anchor,
+}
+
+// Moves the window while keeping its frame size; Applied after geometry
+class WindowPlacement {
+  WindowPlacement({
+    this.policy,
+    this.anchor,
+    this.constrainToWorkArea = false,
+  });
+
+  final WindowPlacementPolicy? policy;
+
+  // Required for anchor policy; Logical screen coordinates
+  final Rect? anchor;
+
+  // Move (and shrink if necessary) the frame to fit display work area
+  final bool constrainToWorkArea;
+
+  Map serialize() => {
+        'policy': policy != null ? enumToString(policy) : null,
+        'anchor': anchor?.serialize(),
+        'constrainToWorkArea': constrainToWorkArea,
+      };
+}
+
+enum WindowLevel {
+  normal,
+  // Always on top of normal windows
+  floating,
+  // Above floating windows; Same as floating on Windows
+  modalPanel,
+}
+
 class WindowStyle {
   WindowStyle({
     this.frame = WindowFrame.regular,
@@ -168,6 +217,10 @@ class WindowStyle {
     this.canMinimize = true,
     this.canMaximize = true,
     this.canFullScreen = true,
+    this.level = WindowLevel.normal,
+    this.skipTaskbar = false,
+    this.opacity = 1.0,
+    this.transparent = false,
   });
 
   final WindowFrame frame;
@@ -178,6 +231,20 @@ class WindowStyle {
   final bool canMaximize; // ignored on mac
   final bool canFullScreen;
 
+  final WindowLevel level;
+
+  // Hide window from taskbar; On macOS excludes the window from Window menu
+  // and window cycling instead (dock icon belongs to the application)
+  final bool skipTaskbar;
+
+  // 0.0 (fully transparent) to 1.0 (opaque); On Windows opacity below 1.0 is
+  // not available for windows using direct composition
+  final double opacity;
+
+  // Only for WindowFrame.noFrame; Areas left transparent by Flutter show what
+  // is behind the window
+  final bool transparent;
+
   dynamic serialize() => {
         'frame': enumToString(frame),
         'canResize': canResize,
@@ -185,6 +252,10 @@ class WindowStyle {
         'canMinimize': canMinimize,
         'canMaximize': canMaximize,
         'canFullScreen': canFullScreen,
+        'level': enumToString(level),
+        'skipTaskbar': skipTaskbar,
+        'opacity': opacity,
+        'transparent': transparent,
       };
 
   static WindowStyle deserialize(dynamic value) {
@@ -196,7 +267,12 @@ class WindowStyle {
         canClose: map['canClose'],
         canMinimize: map['canMinimize'],
         canMaximize: map['canMaximize'],
-        canFullScreen: map['canFullScreen']);
+        canFullScreen: map['canFullScreen'],
+        level: enumFromString(
+            WindowLevel.values, map['level'] ?? '', WindowLevel.normal),
+        skipTaskbar: map['skipTaskbar'] ?? false,
+        opacity: map['opacity'] ?? 1.0,
+        transparent: map['transparent'] ?? false);
   }
 
   @override
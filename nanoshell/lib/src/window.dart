@@ -31,6 +31,47 @@ class WindowHandle {
   String toString() => 'WindowHandle($value)';
 }
 
+// Applied to the window before it is first shown
+class WindowCreateOptions {
+  WindowCreateOptions({
+    this.title,
+    this.style,
+    this.geometry,
+    this.geometryPreference = GeometryPreference.preferContent,
+    this.placement,
+    this.showWhenReady = false,
+    this.persistenceKey,
+  });
+
+  final String? title;
+  final WindowStyle? style;
+  final Geometry? geometry;
+  final GeometryPreference geometryPreference;
+  final WindowPlacement? placement;
+
+  // Show the window as soon as it calls readyToShow, without explicit show call
+  final bool showWhenReady;
+
+  // When set, window geometry and state are saved under this key and restored
+  // next time window with same key is created; Requires window state file to be
+  // configured in ContextOptions
+  final String? persistenceKey;
+
+  dynamic serialize() => {
+        'title': title,
+        'style': style?.serialize(),
+        'geometry': geometry != null || placement != null
+            ? {
+                'geometry': (geometry ?? Geometry()).serialize(),
+                'preference': enumToString(geometryPreference),
+                'placement': placement?.serialize(),
+              }
+            : null,
+        'showWhenReady': showWhenReady,
+        'persistenceKey': persistenceKey,
+      };
+}
+
 class Window {
   Window(this.handle);
 
@@ -60,12 +101,13 @@ class Window {
   }
 
   Future<GeometryFlags> setGeometry(Geometry request,
-      [GeometryPreference preference =
-          GeometryPreference.preferContent]) async {
+      [GeometryPreference preference = GeometryPreference.preferContent,
+      WindowPlacement? placement]) async {
     return GeometryFlags.deserialize(await _invokeMethod(
         Methods.windowSetGeometry, {
       'geometry': request.serialize(),
-      'preference': enumToString(preference)
+      'preference': enumToString(preference),
+      'placement': placement?.serialize(),
     }));
   }
 
@@ -82,6 +124,25 @@ class Window {
     return _invokeMethod(Methods.windowSetStyle, style.serialize());
   }
 
+  Future<void> setTitle(String title) {
+    return _invokeMethod(Methods.windowSetTitle, title);
+  }
+
+  Future<void> setState(WindowState state) {
+    return _invokeMethod(Methods.windowSetState, enumToString(state));
+  }
+
+  Future<WindowState> getState() async {
+    return enumFromString(WindowState.values,
+        await _invokeMethod(Methods.windowGetState), WindowState.normal);
+  }
+
+  Future<void> activate() {
+    return _invokeMethod(Methods.windowActivate);
+  }
+
+  bool get isActive => _active;
+
   static LocalWindow of(BuildContext context) =>
       WindowContext.of(context).window;
 
@@ -92,13 +153,18 @@ class Window {
     return WindowManager.instance.getWindow(handle);
   }
 
-  static Future<Window> create(dynamic initData) {
-    return WindowManager.instance.createWindow(initData);
+  static Future<Window> create(dynamic initData,
+      {WindowCreateOptions? options}) {
+    return WindowManager.instance.createWindow(initData, options: options);
   }
 
   final visibilityChangedEvent = Event<bool>();
   final closeRequestEvent = VoidEvent();
   final closeEvent = VoidEvent();
+  final stateChangedEvent = Event<WindowState>();
+  final activationChangedEvent = Event<bool>();
+  // Fired at most once per frame during live move or resize
+  final geometryChangedEvent = Event<Geometry>();
 
   void onMessage(String message, dynamic arguments) {
     if (message == Events.windowInitialize) {
@@ -111,6 +177,14 @@ class Window {
         _showCompleter!.complete();
         _showCompleter = null;
       }
+    } else if (message == Events.windowStateChanged) {
+      stateChangedEvent.fire(enumFromString(
+          WindowState.values, arguments as String, WindowState.normal));
+    } else if (message == Events.windowActivationChanged) {
+      _active = arguments as bool;
+      activationChangedEvent.fire(_active);
+    } else if (message == Events.windowGeometryChanged) {
+      geometryChangedEvent.fire(Geometry.deserialize(arguments));
     } else if (message == Events.windowClose) {
       WindowManager.instance.windowClosed(this);
       closeEvent.fire();
@@ -136,6 +210,7 @@ class Window {
   final _initializedCompleter = Completer<void>();
   Completer<void>? _showCompleter;
   bool? _visible;
+  bool _active = false;
   bool _initialized = false;
 }
 
@@ -1,3 +1,4 @@
+import 'package:flutter/services.dart';
 import 'package:flutter/widgets.dart';
 
 import 'key_interceptor.dart';
@@ -23,7 +24,27 @@ class WindowManager {
     final result = await dispatcher.invokeMethod(
         channel: Channels.windowManager,
         method: Methods.windowInit,
-        targetWindowHandle: WindowHandle.invalid);
+        targetWindowHandle: WindowHandle.invalid,
+        arguments: {
+          'protocolVersion': Protocol.version,
+        });
+
+    // Native side older than protocol versioning ignores the arguments and
+    // replies without version
+    final protocolVersion = result['protocolVersion'] as int?;
+    if (protocolVersion != Protocol.version) {
+      throw PlatformException(
+          code: ErrorCodes.incompatibleProtocol,
+          message: 'Incompatible nanoshell protocol version '
+              '(native: $protocolVersion, dart: ${Protocol.version}); '
+              'Make sure nanoshell Dart package and crate versions match',
+          details: {
+            'nativeVersion': protocolVersion,
+            'dartVersion': Protocol.version,
+          });
+    }
+    _capabilities = Set.unmodifiable(
+        (result['capabilities'] as List).cast<String>());
 
     _currentWindow = WindowHandle(result['currentWindow'] as int);
     final allWindows = result['allWindows'] as List;
@@ -44,7 +65,8 @@ class WindowManager {
     dispatcher.registerMethodHandler(Channels.dropTarget, _onDropTargetCall);
   }
 
-  Future<Window> createWindow(dynamic initData) async {
+  Future<Window> createWindow(dynamic initData,
+      {WindowCreateOptions? options}) async {
     final dispatcher = WindowMethodDispatcher.instance;
     final result = await dispatcher.invokeMethod(
         channel: Channels.windowManager,
@@ -53,6 +75,7 @@ class WindowManager {
         arguments: {
           'parent': currentWindow.handle.value,
           'initData': initData,
+          if (options != null) 'options': options.serialize(),
         });
     final handle = WindowHandle(result['windowHandle'] as int);
     final res = _windows.putIfAbsent(handle, () => Window(handle));
@@ -64,6 +87,12 @@ class WindowManager {
     _windows.remove(window.handle);
   }
 
+  // Window methods supported by current platform (i.e. Methods.windowShowSystemMenu)
+  Set<String> get capabilities => _capabilities;
+  var _capabilities = <String>{};
+
+  bool hasCapability(String method) => _capabilities.contains(method);
+
   final _windows = <WindowHandle, Window>{};
   WindowHandle? _currentWindow;
 
@@ -71,6 +100,16 @@ class WindowManager {
 
   Window? getWindow(WindowHandle handle) => _windows[handle];
 
+  // Window that currently has keyboard focus, if any
+  Window? get activeWindow {
+    for (final window in _windows.values) {
+      if (window.isActive) {
+        return window;
+      }
+    }
+    return null;
+  }
+
   void _onMessage(WindowMessage message) {
     var window = _windows[message.sourceWindowHandle];
     if (window == null) {
@@ -60,6 +60,10 @@ class WindowMethodDispatcher {
     final res = await _binaryMessenger.send(Channels.dispatcher, encoded);
     final decoded = _codec.decodeMessage(res);
 
+    if (decoded == null) {
+      throw MissingPluginException(
+          'No implementation found for method $method on channel $channel');
+    }
     if (decoded is! Map) {
       throw PlatformException(
           code: 'format', message: 'Invalid response format');
@@ -115,7 +119,8 @@ class WindowMethodDispatcher {
 
         final handler = _methodHandlers[channel];
         if (handler == null) {
-          return _encodeError('missing-handler', 'Missing handler for channel');
+          // null reply is treated as not implemented
+          return null;
         } else {
           final res = await handler(call);
           return _codec.encodeMessage({
@@ -139,6 +144,8 @@ class WindowMethodDispatcher {
         return null;
       }
       return _encodeError('error', 'Malformed message');
+    } on MissingPluginException {
+      return null;
     } on PlatformException catch (e) {
       print(e);
       return _encodeError(e.code, e.message);
@@ -1,11 +1,14 @@
 use std::rc::Rc;
 
+use log::error;
+
 use crate::{
-    shell::{BinaryMessengerReply, Context, EngineHandle, EngineManager},
+    error_code,
+    shell::{BinaryMessengerReply, CallOptions, Context, EngineHandle, EngineManager},
     Error, Result,
 };
 
-use super::MessageCodec;
+use super::{MessageCodec, MethodCallError, Value};
 
 pub struct MessageChannel<V>
 where
@@ -25,6 +28,7 @@ impl<V> MessageChannel<V> {
     ) -> Self
     where
         F: Fn(V, MessageReply<V>) -> () + 'static,
+        V: From<Value>,
     {
         Self::new_with_engine_manager(
             context.clone(),
@@ -46,6 +50,7 @@ impl<V> MessageChannel<V> {
     ) -> Self
     where
         F: Fn(V, MessageReply<V>) -> () + 'static,
+        V: From<Value>,
     {
         let res = MessageChannel {
             context: context.clone(),
@@ -60,12 +65,25 @@ impl<V> MessageChannel<V> {
         let engine = engine_manager.get_engine(engine_handle);
         if let Some(engine) = engine {
             let codec = codec;
+            let channel = channel_name.to_string();
+            let metrics = engine.binary_messenger().metrics().clone();
             engine
                 .binary_messenger()
                 .register_channel_handler(channel_name, move |data, reply| {
-                    let message = codec.decode_message(data).unwrap();
-                    let reply = MessageReply { reply, codec };
-                    callback(message, reply);
+                    match codec.decode_message(data) {
+                        Some(message) => callback(message, MessageReply { reply, codec }),
+                        None => {
+                            metrics.record_error(&channel);
+                            error!("Received malformed message on channel {}", channel);
+                            let error: Value = MethodCallError {
+                                code: error_code::INVALID_REQUEST.into(),
+                                message: Some(format!("Malformed message on channel {}", channel)),
+                                details: Value::U8List(data.into()),
+                            }
+                            .into();
+                            reply.send(&codec.encode_message(&error.into()));
+                        }
+                    }
                 });
         }
         res
@@ -92,9 +110,10 @@ where
 }
 
 impl<V> MessageSender<V> {
+    // Reply is Err(Error::InvalidReply) if it can not be decoded
     pub fn send_message<F>(&self, message: &V, reply: F) -> Result<()>
     where
-        F: FnOnce(V) -> () + 'static,
+        F: FnOnce(Result<V>) + 'static,
     {
         let encoded = self.codec.encode_message(message);
         let engine_manager = self.context.engine_manager.borrow();
@@ -104,11 +123,42 @@ impl<V> MessageSender<V> {
             engine
                 .binary_messenger()
                 .send_message(&self.channel_name, &encoded, move |message| {
-                    let message = codec.decode_message(message).unwrap();
-                    reply(message);
+                    reply(codec.decode_message(message).ok_or(Error::InvalidReply))
                 })
         } else {
-            Err(Error::InvalidEngineHandle)
+            Err(Error::InvalidEngineHandle(self.engine_handle))
+        }
+    }
+
+    // Reply is Err(Error::Timeout) if timeout specified in options elapses
+    // before reply arrives; Callback is dropped if the call gets cancelled.
+    pub fn send_message_with_options<F>(
+        &self,
+        message: &V,
+        options: CallOptions,
+        reply: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(Result<V>) + 'static,
+    {
+        let encoded = self.codec.encode_message(message);
+        let engine_manager = self.context.engine_manager.borrow();
+        let engine = engine_manager.get_engine(self.engine_handle);
+        if let Some(engine) = engine {
+            let codec = self.codec;
+            engine.binary_messenger().send_message_with_options(
+                &self.channel_name,
+                &encoded,
+                options,
+                move |message| {
+                    let message = message.and_then(|message| {
+                        codec.decode_message(message).ok_or(Error::InvalidReply)
+                    });
+                    reply(message);
+                },
+            )
+        } else {
+            Err(Error::InvalidEngineHandle(self.engine_handle))
         }
     }
 
@@ -121,7 +171,7 @@ impl<V> MessageSender<V> {
                 .binary_messenger()
                 .post_message(&self.channel_name, &encoded)
         } else {
-            Err(Error::InvalidEngineHandle)
+            Err(Error::InvalidEngineHandle(self.engine_handle))
         }
     }
 }
@@ -156,3 +206,85 @@ impl<V> Drop for MessageChannel<V> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use velcro::hash_map;
+
+    use crate::{
+        codec::{MessageCodec, StandardMethodCodec, Value},
+        error_code,
+        shell::Context,
+        Error,
+    };
+
+    use super::MessageChannel;
+
+    #[test]
+    fn test_malformed_message() {
+        let context = Context::new(Default::default()).unwrap();
+        let engine = context.engine_manager.borrow_mut().create_engine();
+        let _channel = MessageChannel::new(
+            context.clone(),
+            engine,
+            "channel",
+            &StandardMethodCodec,
+            |_, _| panic!("malformed message must not reach the handler"),
+        );
+
+        let reply = Rc::new(RefCell::new(None));
+        let reply_copy = reply.clone();
+        let engine_manager = context.engine_manager.borrow();
+        let engine = engine_manager.get_engine(engine).unwrap();
+        assert!(engine
+            .binary_messenger()
+            .platform_messenger()
+            .deliver_message("channel", &[200], move |data| {
+                reply_copy.replace(Some(data.to_vec()));
+            }));
+
+        let reply = reply.borrow_mut().take().unwrap();
+        assert_eq!(
+            StandardMethodCodec.decode_message(&reply),
+            Some(Value::Map(hash_map! {
+                "code".into() : error_code::INVALID_REQUEST.into(),
+                "message".into() : "Malformed message on channel channel".into(),
+                "details".into() : Value::U8List(vec![200]),
+            }))
+        );
+    }
+
+    #[test]
+    fn test_malformed_reply() {
+        let context = Context::new(Default::default()).unwrap();
+        let engine = context.engine_manager.borrow_mut().create_engine();
+        let channel = MessageChannel::new(
+            context.clone(),
+            engine,
+            "channel",
+            &StandardMethodCodec,
+            |_, _| {},
+        );
+        context
+            .engine_manager
+            .borrow()
+            .get_engine(engine)
+            .unwrap()
+            .binary_messenger()
+            .platform_messenger()
+            .set_engine_handler("channel", |_, reply| reply.send(&[200]));
+
+        let reply = Rc::new(RefCell::new(None));
+        let reply_copy = reply.clone();
+        channel
+            .sender()
+            .send_message(&Value::Null, move |r| {
+                reply_copy.replace(Some(r));
+            })
+            .unwrap();
+        let reply = reply.borrow_mut().take().unwrap();
+        assert!(matches!(reply, Err(Error::InvalidReply)));
+    }
+}
@@ -1,11 +1,15 @@
-use std::rc::Rc;
+use std::{rc::Rc, time::Instant};
+
+use log::error;
 
 use crate::{
-    shell::{BinaryMessengerReply, Context, EngineHandle, EngineManager},
+    error_code,
+    shell::{BinaryMessengerReply, CallOptions, Context, EngineHandle, EngineManager},
+    util::trace,
     Error, Result,
 };
 
-use super::{MethodCall, MethodCallError, MethodCallResult, MethodCodec};
+use super::{MethodCall, MethodCallError, MethodCallResult, MethodCodec, Value};
 
 pub struct MethodChannel<V>
 where
@@ -25,6 +29,7 @@ impl<V> MethodChannel<V> {
     ) -> Self
     where
         F: Fn(MethodCall<V>, MethodCallReply<V>) -> () + 'static,
+        V: From<Value>,
     {
         Self::new_with_engine_manager(
             context.clone(),
@@ -46,6 +51,7 @@ impl<V> MethodChannel<V> {
     ) -> Self
     where
         F: Fn(MethodCall<V>, MethodCallReply<V>) -> () + 'static,
+        V: From<Value>,
     {
         let res = MethodChannel {
             context: context.clone(),
@@ -60,12 +66,23 @@ impl<V> MethodChannel<V> {
         let engine = engine_manager.get_engine(engine_handle);
         if let Some(engine) = engine {
             let codec = codec;
+            let channel = channel_name.to_string();
+            let metrics = engine.binary_messenger().metrics().clone();
             engine
                 .binary_messenger()
                 .register_channel_handler(channel_name, move |data, reply| {
-                    let message = codec.decode_method_call(data).unwrap();
-                    let reply = MethodCallReply { reply, codec };
-                    callback(message, reply);
+                    match codec.decode_method_call(data) {
+                        Some(call) => callback(call, MethodCallReply { reply, codec }),
+                        None => {
+                            metrics.record_error(&channel);
+                            error!("Received malformed method call on channel {}", channel);
+                            reply.send(&codec.encode_error_envelope(
+                                error_code::INVALID_REQUEST,
+                                Some(&format!("Malformed method call on channel {}", channel)),
+                                &Value::U8List(data.into()).into(),
+                            ));
+                        }
+                    }
                 });
         }
         res
@@ -93,23 +110,68 @@ where
 }
 
 impl<V> MethodInvoker<V> {
+    // V: From<Value> is needed to report errors with details (see MethodCallError)
     pub fn call_method<F>(&self, method: String, args: V, reply: F) -> Result<()>
     where
         F: FnOnce(MethodCallResult<V>) -> () + 'static,
+        V: From<Value>,
+    {
+        self.call_method_with_options(method, args, Default::default(), reply)
+    }
+
+    // Reply is MethodCallError with Timeout code if timeout specified in options
+    // elapses before reply arrives; Callback is dropped if the call gets cancelled.
+    pub fn call_method_with_options<F>(
+        &self,
+        method: String,
+        args: V,
+        options: CallOptions,
+        reply: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(MethodCallResult<V>) + 'static,
+        V: From<Value>,
     {
+        let span = trace::method_call_span(&self.channel_name, &method);
+        let _guard = span.enter();
+        let reply_span = span.clone();
         let encoded = self.codec.encode_method_call(&MethodCall { method, args });
         let engine_manager = self.context.engine_manager.borrow();
         let engine = engine_manager.get_engine(self.engine_handle);
         if let Some(engine) = engine {
             let codec = self.codec;
-            engine
-                .binary_messenger()
-                .send_message(&self.channel_name, &encoded, move |message| {
-                    let message = codec.decode_envelope(message).unwrap();
-                    reply(message);
-                })
+            let metrics = engine.binary_messenger().metrics().clone();
+            let channel = self.channel_name.clone();
+            let start = Instant::now();
+            engine.binary_messenger().send_message_with_options(
+                &self.channel_name,
+                &encoded,
+                options,
+                move |message| {
+                    trace::record_latency(&reply_span, start.elapsed());
+                    let _guard = reply_span.enter();
+                    let result = match message {
+                        Ok([]) => Err(MethodCallError::not_implemented()),
+                        Ok(message) => match codec.decode_envelope(message) {
+                            Some(result) => {
+                                if result.is_err() {
+                                    metrics.record_error(&channel);
+                                }
+                                result
+                            }
+                            None => {
+                                metrics.record_error(&channel);
+                                Err(Error::InvalidReply.into())
+                            }
+                        },
+                        // timeout, already counted by messenger
+                        Err(error) => Err(error.into()),
+                    };
+                    reply(result);
+                },
+            )
         } else {
-            Err(Error::InvalidEngineHandle)
+            Err(Error::InvalidEngineHandle(self.engine_handle))
         }
     }
 }
@@ -136,6 +198,11 @@ impl<V> MethodCallReply<V> {
         self.send(MethodCallResult::Ok(value))
     }
 
+    // Sends empty reply, which Dart reports as MissingPluginException
+    pub fn send_not_implemented(self) {
+        self.reply.send(&[]);
+    }
+
     pub fn send_error(self, code: &str, message: Option<&str>, details: V) {
         self.send(MethodCallResult::Err(MethodCallError {
             code: code.into(),
@@ -156,3 +223,48 @@ impl<V> Drop for MethodChannel<V> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        codec::{MethodCodec, StandardMethodCodec, Value},
+        error_code,
+        shell::Context,
+    };
+
+    use super::MethodChannel;
+
+    #[test]
+    fn test_malformed_call() {
+        let context = Context::new(Default::default()).unwrap();
+        let engine = context.engine_manager.borrow_mut().create_engine();
+        let _channel = MethodChannel::new(
+            context.clone(),
+            engine,
+            "channel",
+            &StandardMethodCodec,
+            |_, _| panic!("malformed call must not reach the handler"),
+        );
+
+        let reply = Rc::new(RefCell::new(None));
+        let reply_copy = reply.clone();
+        let engine_manager = context.engine_manager.borrow();
+        let engine = engine_manager.get_engine(engine).unwrap();
+        assert!(engine
+            .binary_messenger()
+            .platform_messenger()
+            .deliver_message("channel", &[200], move |data| {
+                reply_copy.replace(Some(data.to_vec()));
+            }));
+
+        let reply = reply.borrow_mut().take().unwrap();
+        let error = StandardMethodCodec
+            .decode_envelope(&reply)
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(error.code, error_code::INVALID_REQUEST);
+        assert_eq!(error.details, Value::U8List(vec![200]));
+    }
+}
@@ -27,6 +27,19 @@ pub struct MethodCallError<V> {
 }
 
 impl<V> MethodCallError<V> {
+    // Reported for empty reply, which flutter uses to signal that method is
+    // not implemented
+    pub fn not_implemented() -> Self
+    where
+        V: From<Value>,
+    {
+        Self {
+            code: crate::error_code::NOT_IMPLEMENTED.into(),
+            message: Some("Method not implemented".into()),
+            details: Value::Null.into(),
+        }
+    }
+
     pub fn from_code_message(code: &str, message: &str) -> Self
     where
         V: Default,
@@ -39,19 +52,35 @@ impl<V> MethodCallError<V> {
     }
 }
 
+// Requires V: From<Value> (instead of V: Default) so that error details are
+// preserved; Custom value types need to implement From<Value>. Both bounds can't
+// be supported side by side (Value itself implements Default), and codecs in
+// this crate only work with Value.
 impl<V> From<Error> for MethodCallError<V>
 where
-    V: Default,
+    V: From<Value>,
 {
     fn from(e: Error) -> Self {
         Self {
-            code: format!("{:?}", e),
+            code: e.code().into(),
             message: Some(format!("{}", e)),
-            details: Default::default(),
+            details: e.details().into(),
         }
     }
 }
 
+// Error map ({ code, message, details }) used to report errors over plain
+// message channels
+impl From<MethodCallError<Value>> for Value {
+    fn from(e: MethodCallError<Value>) -> Self {
+        Value::Map(velcro::hash_map! {
+            "code".into() : e.code.into(),
+            "message".into() : e.message.map(Value::String).unwrap_or(Value::Null),
+            "details".into() : e.details,
+        })
+    }
+}
+
 pub trait MessageCodec<V>: Send + Sync {
     /// Methods for plain messages
     fn encode_message(&self, v: &V) -> Vec<u8>;
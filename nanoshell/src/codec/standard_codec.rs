@@ -1,10 +1,10 @@
-use std::{collections::HashMap, slice};
+use std::collections::HashMap;
 
 use log::error;
 
 // Based on code from flutter-rs
 
-use super::{MessageCodec, MethodCall, MethodCallResult, MethodCodec, MethodCallError, Value};
+use super::{MessageCodec, MethodCall, MethodCallError, MethodCallResult, MethodCodec, Value};
 
 const VALUE_NULL: u8 = 0;
 const VALUE_TRUE: u8 = 1;
@@ -52,10 +52,10 @@ impl MethodCodec<Value> for StandardMethodCodec {
 
     fn decode_method_call(&self, buf: &[u8]) -> Option<MethodCall<Value>> {
         let mut reader = Reader::new(buf);
-        let method: Value = StandardMethodCodec::read_value(&mut reader).unwrap();
-        let args: Value = StandardMethodCodec::read_value(&mut reader).unwrap();
+        let method = StandardMethodCodec::read_value(&mut reader);
+        let args = StandardMethodCodec::read_value(&mut reader);
 
-        if let Value::String(method) = method {
+        if let (Ok(Value::String(method)), Ok(args)) = (method, args) {
             return Some(MethodCall { method, args });
         }
         error!("Invalid method call");
@@ -85,14 +85,14 @@ impl MethodCodec<Value> for StandardMethodCodec {
 
     fn decode_envelope(&self, buf: &[u8]) -> Option<MethodCallResult<Value>> {
         let mut reader = Reader::new(buf);
-        let n = reader.read_u8();
+        let n = reader.read_u8().ok()?;
         if n == 0 {
-            let ret = StandardMethodCodec::read_value(&mut reader).unwrap();
+            let ret = StandardMethodCodec::read_value(&mut reader).ok()?;
             Some(MethodCallResult::Ok(ret))
         } else if n == 1 {
-            let code = StandardMethodCodec::read_value(&mut reader).unwrap();
-            let message = StandardMethodCodec::read_value(&mut reader).unwrap();
-            let details = StandardMethodCodec::read_value(&mut reader).unwrap();
+            let code = StandardMethodCodec::read_value(&mut reader).ok()?;
+            let message = StandardMethodCodec::read_value(&mut reader).ok()?;
+            let details = StandardMethodCodec::read_value(&mut reader).ok()?;
             Some(MethodCallResult::Err(MethodCallError {
                 code: match code {
                     Value::String(s) => s,
@@ -116,67 +116,60 @@ impl StandardMethodCodec {
             return Err(DecodeError::Ended);
         }
 
-        let t = reader.read_u8();
+        let t = reader.read_u8()?;
         Ok(match t {
             VALUE_NULL => Value::Null,
             VALUE_FALSE => Value::Bool(false),
             VALUE_TRUE => Value::Bool(true),
-            VALUE_INT32 => Value::I64(reader.read_i32().into()),
-            VALUE_INT64 => Value::I64(reader.read_i64()),
-            VALUE_LARGEINT => panic!("Not implemented"),
+            VALUE_INT32 => Value::I64(reader.read_i32()?.into()),
+            VALUE_INT64 => Value::I64(reader.read_i64()?),
             VALUE_FLOAT64 => {
                 reader.align_to(8);
-                Value::F64(reader.read_f64())
+                Value::F64(reader.read_f64()?)
             }
             VALUE_STRING => {
-                let len = reader.read_size();
-                Value::String(reader.read_string(len))
+                let len = reader.read_size()?;
+                Value::String(reader.read_string(len)?)
             }
             VALUE_UINT8LIST => {
-                let len = reader.read_size();
-                Value::U8List(reader.read_u8_list(len))
+                let len = reader.read_size()?;
+                Value::U8List(reader.read_u8_list(len)?)
             }
             VALUE_INT32LIST => {
-                let len = reader.read_size();
-                Value::I32List(reader.read_i32_list(len))
+                let len = reader.read_size()?;
+                Value::I32List(reader.read_i32_list(len)?)
             }
             VALUE_INT64LIST => {
-                let len = reader.read_size();
-                Value::I64List(reader.read_i64_list(len))
+                let len = reader.read_size()?;
+                Value::I64List(reader.read_i64_list(len)?)
             }
             VALUE_FLOAT64LIST => {
-                let len = reader.read_size();
-                Value::F64List(reader.read_f64_list(len))
+                let len = reader.read_size()?;
+                Value::F64List(reader.read_f64_list(len)?)
             }
             VALUE_LIST => {
-                let len = reader.read_size();
-                let mut list = Vec::new();
+                let len = reader.read_size()?;
+                let mut list = Vec::with_capacity(reader.capacity(len, 1));
                 for _ in 0..len {
-                    if let Ok(e) = Self::read_value(reader) {
-                        list.push(e);
-                    } else {
-                        return Err(DecodeError::Invalid);
-                    }
+                    list.push(Self::read_value(reader)?);
                 }
                 Value::List(list)
             }
             VALUE_MAP => {
-                let len = reader.read_size();
+                let len = reader.read_size()?;
                 let mut map = HashMap::new();
                 for _ in 0..len {
-                    let k = Self::read_value(reader);
-                    let v = Self::read_value(reader);
-                    if k.is_err() || v.is_err() {
-                        return Err(DecodeError::Invalid);
-                    }
-                    let k = k.unwrap();
-                    let v = v.unwrap();
-
+                    let k = Self::read_value(reader)?;
+                    let v = Self::read_value(reader)?;
                     map.insert(k, v);
                 }
                 Value::Map(map)
             }
-            _ => Value::Null,
+            VALUE_LARGEINT => {
+                error!("Large integers are not supported");
+                return Err(DecodeError::Invalid);
+            }
+            _ => return Err(DecodeError::Invalid),
         })
     }
     fn write_string(writer: &mut Writer, s: &str) {
@@ -325,90 +318,79 @@ impl<'a> Reader<'a> {
     fn new(buf: &'a [u8]) -> Self {
         Reader { buf, pos: 0 }
     }
-    fn read_u8(&mut self) -> u8 {
-        let n = self.buf[self.pos];
-        self.pos += 1;
-        n
-    }
-    fn read_u16(&mut self) -> u16 {
-        self.pos += 2;
-        let s = &self.buf[self.pos - 2..self.pos];
-        u16::from_ne_bytes(clone_into_array(s))
-    }
-    fn read_u32(&mut self) -> u32 {
-        self.pos += 4;
-        let s = &self.buf[self.pos - 4..self.pos];
-        u32::from_ne_bytes(clone_into_array(s))
-    }
-    fn read_i32(&mut self) -> i32 {
-        self.pos += 4;
-        let s = &self.buf[self.pos - 4..self.pos];
-        i32::from_ne_bytes(clone_into_array(s))
-    }
-    fn read_u64(&mut self) -> u64 {
-        self.pos += 8;
-        let s = &self.buf[self.pos - 8..self.pos];
-        u64::from_ne_bytes(clone_into_array(s))
-    }
-    fn read_i64(&mut self) -> i64 {
-        self.pos += 8;
-        let s = &self.buf[self.pos - 8..self.pos];
-        i64::from_ne_bytes(clone_into_array(s))
-    }
-    fn read_f64(&mut self) -> f64 {
-        let n = self.read_u64();
-        f64::from_bits(n)
-    }
-    fn read_size(&mut self) -> usize {
-        let n = self.read_u8();
-        match n {
-            254 => self.read_u16() as usize,
-            255 => self.read_u32() as usize,
-            _ => n as usize,
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self.pos.checked_add(len).ok_or(DecodeError::Ended)?;
+        if end > self.buf.len() {
+            return Err(DecodeError::Ended);
         }
+        let res = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(res)
     }
-    fn read_string(&mut self, len: usize) -> String {
-        unsafe {
-            if len == 0 {
-                String::from("")
-            } else {
-                let v = slice::from_raw_parts(&self.buf[self.pos], len);
-                self.pos += len;
-                String::from_utf8_lossy(v).to_owned().to_string()
-            }
-        }
+    fn read_u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.read_bytes(1)?[0])
     }
-    fn read_u8_list(&mut self, len: usize) -> Vec<u8> {
-        let mut v = Vec::with_capacity(len);
-        for _ in 0..len {
-            v.push(self.read_u8());
-        }
-        v
+    fn read_u16(&mut self) -> Result<u16, DecodeError> {
+        Ok(u16::from_ne_bytes(clone_into_array(self.read_bytes(2)?)))
+    }
+    fn read_u32(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_ne_bytes(clone_into_array(self.read_bytes(4)?)))
+    }
+    fn read_i32(&mut self) -> Result<i32, DecodeError> {
+        Ok(i32::from_ne_bytes(clone_into_array(self.read_bytes(4)?)))
+    }
+    fn read_u64(&mut self) -> Result<u64, DecodeError> {
+        Ok(u64::from_ne_bytes(clone_into_array(self.read_bytes(8)?)))
+    }
+    fn read_i64(&mut self) -> Result<i64, DecodeError> {
+        Ok(i64::from_ne_bytes(clone_into_array(self.read_bytes(8)?)))
+    }
+    fn read_f64(&mut self) -> Result<f64, DecodeError> {
+        Ok(f64::from_bits(self.read_u64()?))
     }
-    fn read_i32_list(&mut self, len: usize) -> Vec<i32> {
-        let mut v = Vec::with_capacity(len);
+    fn read_size(&mut self) -> Result<usize, DecodeError> {
+        let n = self.read_u8()?;
+        Ok(match n {
+            254 => self.read_u16()? as usize,
+            255 => self.read_u32()? as usize,
+            _ => n as usize,
+        })
+    }
+    fn read_string(&mut self, len: usize) -> Result<String, DecodeError> {
+        let v = self.read_bytes(len)?;
+        Ok(String::from_utf8_lossy(v).to_string())
+    }
+    fn read_u8_list(&mut self, len: usize) -> Result<Vec<u8>, DecodeError> {
+        Ok(self.read_bytes(len)?.into())
+    }
+    // Capacity for list of given length, bounded by remaining data so that
+    // malformed length can't cause huge allocation
+    fn capacity(&self, len: usize, element_size: usize) -> usize {
+        len.min(self.buf.len().saturating_sub(self.pos) / element_size)
+    }
+    fn read_i32_list(&mut self, len: usize) -> Result<Vec<i32>, DecodeError> {
         self.align_to(4);
+        let mut v = Vec::with_capacity(self.capacity(len, 4));
         for _ in 0..len {
-            v.push(self.read_i32());
+            v.push(self.read_i32()?);
         }
-        v
+        Ok(v)
     }
-    fn read_i64_list(&mut self, len: usize) -> Vec<i64> {
-        let mut v = Vec::with_capacity(len);
+    fn read_i64_list(&mut self, len: usize) -> Result<Vec<i64>, DecodeError> {
         self.align_to(8);
+        let mut v = Vec::with_capacity(self.capacity(len, 8));
         for _ in 0..len {
-            v.push(self.read_i64());
+            v.push(self.read_i64()?);
         }
-        v
+        Ok(v)
     }
-    fn read_f64_list(&mut self, len: usize) -> Vec<f64> {
-        let mut v = Vec::with_capacity(len);
+    fn read_f64_list(&mut self, len: usize) -> Result<Vec<f64>, DecodeError> {
         self.align_to(8);
+        let mut v = Vec::with_capacity(self.capacity(len, 8));
         for _ in 0..len {
-            let n = self.read_u64();
-            v.push(f64::from_bits(n));
+            v.push(self.read_f64()?);
         }
-        v
+        Ok(v)
     }
     fn ended(&self) -> bool {
         self.pos >= self.buf.len()
@@ -430,3 +412,49 @@ where
     <A as AsMut<[T]>>::as_mut(&mut a).clone_from_slice(slice);
     a
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{MessageCodec, MethodCodec, StandardMethodCodec, Value};
+
+    #[test]
+    fn test_truncated() {
+        let codec = StandardMethodCodec;
+        let encoded = MessageCodec::encode_message(
+            &codec,
+            &Value::List(vec![
+                Value::String("Hello".into()),
+                Value::I64(1 << 40),
+                Value::F64(1.5),
+                Value::I32List(vec![1, 2, 3]),
+                Value::F64List(vec![1.0, 2.0]),
+            ]),
+        );
+        assert!(codec.decode_message(&encoded).is_some());
+        for len in 0..encoded.len() {
+            assert!(codec.decode_message(&encoded[..len]).is_none());
+        }
+    }
+
+    #[test]
+    fn test_invalid() {
+        let codec = StandardMethodCodec;
+        // unknown type
+        assert!(codec.decode_message(&[200]).is_none());
+        // string with length beyond buffer
+        assert!(codec
+            .decode_message(&[7, 255, 255, 255, 255, 127])
+            .is_none());
+        // list with huge length
+        assert!(codec
+            .decode_message(&[12, 255, 255, 255, 255, 255])
+            .is_none());
+        // method call with non-string method
+        assert!(codec.decode_method_call(&[3, 1, 0, 0, 0, 0]).is_none());
+        assert!(codec.decode_method_call(&[]).is_none());
+        // empty / unknown envelopes
+        assert!(codec.decode_envelope(&[]).is_none());
+        assert!(codec.decode_envelope(&[2, 0]).is_none());
+        assert!(codec.decode_envelope(&[1, 7, 1]).is_none());
+    }
+}
@@ -95,6 +95,11 @@ impl<'a, 'de> SeqAccess<'a, 'de> {
     pub fn new(de: &'a mut Deserializer<'de>) -> Self {
         Self { de, index: 0 }
     }
+
+    // Path segment of last returned element
+    fn element(&self) -> String {
+        format!("[{}]", self.index - 1)
+    }
 }
 
 impl<'a, 'de> serde::de::SeqAccess<'de> for SeqAccess<'a, 'de> {
@@ -111,7 +116,8 @@ impl<'a, 'de> serde::de::SeqAccess<'de> for SeqAccess<'a, 'de> {
                 }
                 self.index += 1;
                 Ok(Some(
-                    seed.deserialize(vec[self.index - 1].into_deserializer())?,
+                    seed.deserialize(vec[self.index - 1].into_deserializer())
+                        .map_err(|e: ValueError| e.in_field(&self.element()))?,
                 ))
             }
             Value::I64List(vec) => {
@@ -120,7 +126,8 @@ impl<'a, 'de> serde::de::SeqAccess<'de> for SeqAccess<'a, 'de> {
                 }
                 self.index += 1;
                 Ok(Some(
-                    seed.deserialize(vec[self.index - 1].into_deserializer())?,
+                    seed.deserialize(vec[self.index - 1].into_deserializer())
+                        .map_err(|e: ValueError| e.in_field(&self.element()))?,
                 ))
             }
             Value::F64List(vec) => {
@@ -129,7 +136,8 @@ impl<'a, 'de> serde::de::SeqAccess<'de> for SeqAccess<'a, 'de> {
                 }
                 self.index += 1;
                 Ok(Some(
-                    seed.deserialize(vec[self.index - 1].into_deserializer())?,
+                    seed.deserialize(vec[self.index - 1].into_deserializer())
+                        .map_err(|e: ValueError| e.in_field(&self.element()))?,
                 ))
             }
             Value::List(vec) => {
@@ -137,9 +145,10 @@ impl<'a, 'de> serde::de::SeqAccess<'de> for SeqAccess<'a, 'de> {
                     return Ok(None);
                 }
                 self.index += 1;
-                Ok(Some(seed.deserialize(&mut Deserializer::new(
-                    &vec[self.index - 1],
-                ))?))
+                Ok(Some(
+                    seed.deserialize(&mut Deserializer::new(&vec[self.index - 1]))
+                        .map_err(|e| e.in_field(&self.element()))?,
+                ))
             }
             _ => Err(ValueError::NoList),
         }
@@ -149,6 +158,7 @@ impl<'a, 'de> serde::de::SeqAccess<'de> for SeqAccess<'a, 'de> {
 struct MapAccess<'a, 'de> {
     de: &'a mut Deserializer<'de>,
     key_iter: Keys<'de, Value, Value>,
+    next_key: Option<&'de Value>,
     next_value: Option<&'de Value>,
 }
 
@@ -162,6 +172,7 @@ impl<'a, 'de> MapAccess<'a, 'de> {
         Self {
             de,
             key_iter: map.keys(),
+            next_key: None,
             next_value: None,
         }
     }
@@ -181,6 +192,7 @@ impl<'a, 'de> serde::de::MapAccess<'de> for MapAccess<'a, 'de> {
                 } else {
                     return Ok(None);
                 };
+                self.next_key.replace(next);
                 self.next_value.replace(map.get(next).unwrap());
                 let deserializer = &mut Deserializer::new(next);
                 Ok(Some(seed.deserialize(deserializer)?))
@@ -193,7 +205,14 @@ impl<'a, 'de> serde::de::MapAccess<'de> for MapAccess<'a, 'de> {
     where
         V: serde::de::DeserializeSeed<'de>,
     {
+        let key = self.next_key.take();
         seed.deserialize(&mut Deserializer::new(self.next_value.take().unwrap()))
+            .map_err(|e| match key {
+                Some(Value::String(key)) => e.in_field(key),
+                Some(Value::I64(key)) => e.in_field(&key.to_string()),
+                Some(key) => e.in_field(&format!("{:?}", key)),
+                None => e,
+            })
     }
 }
 
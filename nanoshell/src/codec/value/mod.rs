@@ -7,6 +7,9 @@ use std::collections::HashMap;
 use std::hash::Hash;
 
 use serde;
+use velcro::hash_map;
+
+use crate::error_code;
 
 pub use self::deserializer::{from_value, from_value_owned};
 pub use self::serializer::to_value;
@@ -126,6 +129,50 @@ pub enum ValueError {
     WrongType,
     NoList,
     NoMap,
+    // Error in map field or list element; path is i.e. "geometry.frame[2]"
+    Field {
+        path: String,
+        error: Box<ValueError>,
+    },
+}
+
+impl ValueError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ValueError::Message(_) => error_code::VALUE_ERROR,
+            ValueError::ConversionError => error_code::VALUE_CONVERSION_ERROR,
+            ValueError::WrongType => error_code::VALUE_WRONG_TYPE,
+            ValueError::NoList => error_code::VALUE_NOT_A_LIST,
+            ValueError::NoMap => error_code::VALUE_NOT_A_MAP,
+            ValueError::Field { error, .. } => error.code(),
+        }
+    }
+
+    pub fn details(&self) -> Value {
+        match self {
+            ValueError::Field { path, .. } => Value::Map(hash_map! {
+                "path".into() : path.as_str().into(),
+            }),
+            _ => Value::Null,
+        }
+    }
+
+    // Prepends field name or list index ("[index]") to error path
+    pub(crate) fn in_field(self, field: &str) -> Self {
+        match self {
+            ValueError::Field { path, error } => {
+                let separator = if path.starts_with('[') { "" } else { "." };
+                ValueError::Field {
+                    path: format!("{}{}{}", field, separator, path),
+                    error,
+                }
+            }
+            error => ValueError::Field {
+                path: field.into(),
+                error: Box::new(error),
+            },
+        }
+    }
 }
 
 impl fmt::Display for ValueError {
@@ -136,6 +183,7 @@ impl fmt::Display for ValueError {
             ValueError::WrongType => write!(f, "Value is of wrong type"),
             ValueError::NoList => write!(f, "Value is not a list"),
             ValueError::NoMap => write!(f, "Value is not a map"),
+            ValueError::Field { path, error } => write!(f, "{} (at {})", error, path),
         }
     }
 }
@@ -289,3 +337,50 @@ impl<'de> serde::Deserialize<'de> for Value {
         deserializer.deserialize_any(ValueVisitor)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use velcro::hash_map;
+
+    use super::{from_value, Value, ValueError};
+    use crate::error_code;
+
+    #[derive(serde::Deserialize, Debug)]
+    struct Item {
+        name: String,
+    }
+
+    #[derive(serde::Deserialize, Debug)]
+    struct Outer {
+        items: Vec<Item>,
+    }
+
+    #[test]
+    fn test_error_path() {
+        let outer: Outer = from_value(&Value::Map(hash_map! {
+            "items".into() : Value::List(vec![
+                Value::Map(hash_map! { "name".into() : "first".into() }),
+            ]),
+        }))
+        .unwrap();
+        assert_eq!(outer.items[0].name, "first");
+
+        let value = Value::Map(hash_map! {
+            "items".into() : Value::List(vec![
+                Value::Map(hash_map! { "name".into() : "first".into() }),
+                Value::Map(hash_map! { "name".into() : 10i64.into() }),
+            ]),
+        });
+        let error = from_value::<Outer>(&value).unwrap_err();
+        assert!(matches!(&error, ValueError::Field { path, .. } if path == "items[1].name"));
+        assert_eq!(error.code(), error_code::VALUE_ERROR);
+        let path = match error.details() {
+            Value::Map(mut details) => details.remove(&"path".into()),
+            _ => None,
+        };
+        assert!(matches!(path, Some(Value::String(path)) if path == "items[1].name"));
+
+        let error = from_value::<Outer>(&Value::Null).unwrap_err();
+        assert!(matches!(error.details(), Value::Null));
+    }
+}
@@ -1,25 +1,181 @@
 use std::fmt::Display;
 
-use crate::{codec::value::ValueError, shell::platform::error::PlatformError};
+use velcro::hash_map;
+
+use crate::{
+    codec::{value::ValueError, Value},
+    shell::{platform::error::PlatformError, EngineHandle, MenuHandle, WindowHandle},
+};
+
+// Error codes reported to Dart in MethodCallError; These are stable and should
+// never change. Must be kept in sync with ErrorCodes in lib/src/constants.dart.
+pub mod error_code {
+    // Error::InvalidEngineHandle; details: { engineHandle }
+    pub const INVALID_ENGINE_HANDLE: &str = "invalid-engine-handle";
+
+    // Error::InvalidWindowHandle; details: { windowHandle }
+    pub const INVALID_WINDOW_HANDLE: &str = "invalid-window-handle";
+
+    // Error::InvalidMenuHandle; details: { menuHandle }
+    pub const INVALID_MENU_HANDLE: &str = "invalid-menu-handle";
+
+    // Error::EngineShutDown - engine was shut down before message could be sent
+    pub const ENGINE_SHUT_DOWN: &str = "engine-shut-down";
+
+    // Error::Timeout - no reply was received in time
+    pub const TIMEOUT: &str = "timeout";
+
+    // Error::InvalidReply - reply could not be decoded
+    pub const INVALID_REPLY: &str = "invalid-reply";
+
+    // Error::OutboundQueueFull - too many messages queued for uninitialized window;
+    // details: { windowHandle }
+    pub const OUTBOUND_QUEUE_FULL: &str = "outbound-queue-full";
+
+    // Request could not be decoded or had unexpected arguments; details: offending value
+    pub const INVALID_REQUEST: &str = "invalid-request";
+
+    // Dart package and native crate use different protocol versions;
+    // details: { nativeVersion, dartVersion }
+    pub const INCOMPATIBLE_PROTOCOL: &str = "incompatible-protocol";
+
+    // Engine that sent the request has no window associated
+    pub const NO_WINDOW: &str = "no-window";
+
+    // Method is not implemented (empty reply); In Dart this is reported as
+    // MissingPluginException rather than PlatformException
+    pub const NOT_IMPLEMENTED: &str = "not-implemented";
+
+    // ValueError::Message - value (de)serialization failed; For all value errors
+    // in nested field or list element details are { path }
+    pub const VALUE_ERROR: &str = "value-error";
+
+    // ValueError::ConversionError
+    pub const VALUE_CONVERSION_ERROR: &str = "value-conversion-error";
+
+    // ValueError::WrongType
+    pub const VALUE_WRONG_TYPE: &str = "value-wrong-type";
+
+    // ValueError::NoList
+    pub const VALUE_NOT_A_LIST: &str = "value-not-a-list";
+
+    // ValueError::NoMap
+    pub const VALUE_NOT_A_MAP: &str = "value-not-a-map";
+
+    // PlatformError::UnknownError
+    pub const PLATFORM_UNKNOWN_ERROR: &str = "platform-unknown-error";
+
+    // PlatformError::NotImplemented - feature not implemented on current platform
+    pub const PLATFORM_NOT_IMPLEMENTED: &str = "platform-not-implemented";
+
+    // PlatformError::NotAvailable - feature not available in current state
+    pub const PLATFORM_NOT_AVAILABLE: &str = "platform-not-available";
+
+    // PlatformError::LaunchEngineFailure
+    pub const PLATFORM_LAUNCH_ENGINE_FAILURE: &str = "platform-launch-engine-failure";
+
+    // PlatformError::SendMessageFailure; details: { channel }
+    pub const PLATFORM_SEND_MESSAGE_FAILURE: &str = "platform-send-message-failure";
+
+    // PlatformError::NoEventFound (macOS) - action requires prior mouse event
+    pub const PLATFORM_NO_EVENT_FOUND: &str = "platform-no-event-found";
+
+    // PlatformError::HResult (Windows); details: { hresult }
+    pub const PLATFORM_HRESULT: &str = "platform-hresult";
+}
 
 #[derive(Debug, Clone)]
 pub enum Error {
-    InvalidEngineHandle,
+    InvalidEngineHandle(EngineHandle),
+    InvalidWindowHandle(WindowHandle),
     Platform(PlatformError),
     Value(ValueError),
-    InvalidMenuHandle,
+    InvalidMenuHandle(MenuHandle),
+    EngineShutDown,
+    Timeout,
+    InvalidReply,
+    OutboundQueueFull(WindowHandle),
+}
+
+impl Error {
+    // Stable error code (see error_code module)
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::InvalidEngineHandle(_) => error_code::INVALID_ENGINE_HANDLE,
+            Error::InvalidWindowHandle(_) => error_code::INVALID_WINDOW_HANDLE,
+            Error::Platform(error) => error.code(),
+            Error::Value(error) => error.code(),
+            Error::InvalidMenuHandle(_) => error_code::INVALID_MENU_HANDLE,
+            Error::EngineShutDown => error_code::ENGINE_SHUT_DOWN,
+            Error::Timeout => error_code::TIMEOUT,
+            Error::InvalidReply => error_code::INVALID_REPLY,
+            Error::OutboundQueueFull(_) => error_code::OUTBOUND_QUEUE_FULL,
+        }
+    }
+
+    // Whether the requested functionality is not implemented on current platform;
+    // Such errors are reported to Dart as not implemented replies.
+    pub fn is_not_implemented(&self) -> bool {
+        matches!(
+            self.code(),
+            error_code::PLATFORM_NOT_IMPLEMENTED | error_code::PLATFORM_NOT_AVAILABLE
+        )
+    }
+
+    // Structured details for the error, Value::Null if there are none
+    pub fn details(&self) -> Value {
+        match self {
+            Error::InvalidEngineHandle(handle) => Value::Map(hash_map! {
+                "engineHandle".into() : handle.0.into(),
+            }),
+            Error::InvalidWindowHandle(handle) | Error::OutboundQueueFull(handle) => {
+                Value::Map(hash_map! {
+                    "windowHandle".into() : handle.0.into(),
+                })
+            }
+            Error::Platform(error) => error.details(),
+            Error::Value(error) => error.details(),
+            Error::InvalidMenuHandle(handle) => Value::Map(hash_map! {
+                "menuHandle".into() : handle.0.into(),
+            }),
+            Error::EngineShutDown | Error::Timeout | Error::InvalidReply => Value::Null,
+        }
+    }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::Platform(error) => Display::fmt(error, f),
-            Error::InvalidEngineHandle => {
-                write!(f, "Provided handle does not match any engine")
+            Error::InvalidEngineHandle(handle) => {
+                write!(f, "Provided handle {} does not match any engine", handle.0)
+            }
+            Error::InvalidWindowHandle(handle) => {
+                write!(f, "Provided handle {} does not match any window", handle.0)
             }
             Error::Value(error) => Display::fmt(error, f),
-            Error::InvalidMenuHandle => {
-                write!(f, "Provided menu handle does not match any known menu")
+            Error::InvalidMenuHandle(handle) => {
+                write!(
+                    f,
+                    "Provided menu handle {} does not match any known menu",
+                    handle.0
+                )
+            }
+            Error::EngineShutDown => {
+                write!(f, "Engine has been shut down")
+            }
+            Error::Timeout => {
+                write!(f, "Operation timed out")
+            }
+            Error::InvalidReply => {
+                write!(f, "Received malformed reply")
+            }
+            Error::OutboundQueueFull(handle) => {
+                write!(
+                    f,
+                    "Too many messages queued for uninitialized window {}",
+                    handle.0
+                )
             }
         }
     }
@@ -40,3 +196,15 @@ impl From<ValueError> for Error {
         Error::Value(src)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_not_implemented() {
+        assert!(Error::Platform(PlatformError::NotImplemented).is_not_implemented());
+        assert!(!Error::Platform(PlatformError::UnknownError).is_not_implemented());
+        assert!(!Error::Timeout.is_not_implemented());
+    }
+}
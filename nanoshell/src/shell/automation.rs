@@ -0,0 +1,381 @@
+// Local JSON-RPC 2.0 server for driving the application from end-to-end tests.
+//
+// Only available with "automation" feature on unix platforms, and only started
+// when NANOSHELL_AUTOMATION_SOCKET environment variable is set to socket path.
+// Each request is single line JSON object, answered by single line response.
+//
+// Methods:
+//   listWindows                                       -> [WindowInfo]
+//   getGeometry { window }                            -> WindowGeometry
+//   invokeMenuAction { menu, id }                     -> null
+//   performDrop { window, location, data, allowedEffects } -> null
+//   requestClose { window }                           -> null
+//   diagnostics                                       -> Diagnostics
+//   channelStats                                      -> { channel: ChannelStats }
+
+use std::{
+    cell::RefCell,
+    env, fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::{
+        fs::FileTypeExt,
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    rc::{Rc, Weak},
+    sync::{mpsc, Arc},
+    thread,
+};
+
+use log::{error, info, warn};
+use serde_json::{json, Value as JsonValue};
+
+use crate::Error;
+
+use super::{
+    structs::DraggingInfo, Context, MenuHandle, PlatformWindowDelegate, RunLoopSender, Window,
+    WindowHandle,
+};
+
+const SOCKET_ENV_VAR: &str = "NANOSHELL_AUTOMATION_SOCKET";
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const APPLICATION_ERROR: i64 = -32000;
+
+thread_local! {
+    // Requests are executed on run loop thread, which is where the context lives
+    static CONTEXT: RefCell<Weak<Context>> = const { RefCell::new(Weak::new()) };
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+    data: JsonValue,
+}
+
+impl RpcError {
+    fn new(code: i64, message: String) -> Self {
+        Self {
+            code,
+            message,
+            data: JsonValue::Null,
+        }
+    }
+}
+
+impl From<Error> for RpcError {
+    fn from(error: Error) -> Self {
+        Self {
+            code: APPLICATION_ERROR,
+            message: error.to_string(),
+            data: json!({ "code": error.code() }),
+        }
+    }
+}
+
+type RpcResult = std::result::Result<JsonValue, RpcError>;
+
+pub(super) fn start_from_env(context: &Rc<Context>) {
+    let path = match env::var_os(SOCKET_ENV_VAR) {
+        Some(path) => PathBuf::from(path),
+        None => return,
+    };
+    CONTEXT.with(|c| c.replace(Rc::downgrade(context)));
+
+    if let Err(err) = remove_stale_socket(&path) {
+        error!(
+            "Failed to start automation server on {}: {}",
+            path.display(),
+            err
+        );
+        return;
+    }
+    let listener = match UnixListener::bind(&path) {
+        Ok(listener) => listener,
+        Err(err) => {
+            error!(
+                "Failed to start automation server on {}: {}",
+                path.display(),
+                err
+            );
+            return;
+        }
+    };
+    info!("Automation server listening on {}", path.display());
+
+    let sender = Arc::new(context.run_loop.borrow().new_sender());
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let sender = sender.clone();
+                    thread::spawn(move || serve_connection(stream, sender));
+                }
+                Err(err) => warn!("Automation server connection failed: {}", err),
+            }
+        }
+    });
+}
+
+// Removes socket left behind by previous run; Fails if the path is not a socket
+// or another process is still listening on it
+fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    if !metadata.file_type().is_socket() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            "path exists and is not a socket",
+        ));
+    }
+    if UnixStream::connect(path).is_ok() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            "socket is in use by another process",
+        ));
+    }
+    fs::remove_file(path)
+}
+
+fn serve_connection(stream: UnixStream, sender: Arc<RunLoopSender>) {
+    let mut writer = match stream.try_clone() {
+        Ok(writer) => writer,
+        Err(err) => {
+            warn!("Automation server connection failed: {}", err);
+            return;
+        }
+    };
+    for line in BufReader::new(stream).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => return,
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = handle_line(&line, &sender);
+        if writeln!(writer, "{}", response).is_err() {
+            return;
+        }
+    }
+}
+
+fn handle_line(line: &str, sender: &RunLoopSender) -> JsonValue {
+    let request: JsonValue = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(err) => {
+            return error_response(JsonValue::Null, RpcError::new(PARSE_ERROR, err.to_string()))
+        }
+    };
+    let id = request.get("id").cloned().unwrap_or(JsonValue::Null);
+    let method = match request.get("method").and_then(|m| m.as_str()) {
+        Some(method) => method.to_string(),
+        None => return error_response(id, RpcError::new(INVALID_REQUEST, "Missing method".into())),
+    };
+    let params = request.get("params").cloned().unwrap_or(JsonValue::Null);
+
+    let (tx, rx) = mpsc::channel();
+    sender.send(move || {
+        let context = CONTEXT.with(|c| c.borrow().upgrade());
+        let result = match context {
+            Some(context) => handle_request(&context, &method, params),
+            None => Err(RpcError::new(
+                APPLICATION_ERROR,
+                "Context no longer exists".into(),
+            )),
+        };
+        let _ = tx.send(result);
+    });
+    match rx.recv() {
+        Ok(Ok(result)) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Ok(Err(error)) => error_response(id, error),
+        Err(_) => error_response(
+            id,
+            RpcError::new(APPLICATION_ERROR, "Run loop terminated".into()),
+        ),
+    }
+}
+
+fn error_response(id: JsonValue, error: RpcError) -> JsonValue {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {
+            "code": error.code,
+            "message": error.message,
+            "data": error.data,
+        }
+    })
+}
+
+fn param<T: serde::de::DeserializeOwned>(params: &JsonValue, name: &str) -> Result<T, RpcError> {
+    let value = params.get(name).cloned().unwrap_or(JsonValue::Null);
+    serde_json::from_value(value).map_err(|err| {
+        RpcError::new(
+            INVALID_PARAMS,
+            format!("Invalid parameter '{}': {}", name, err),
+        )
+    })
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> RpcResult {
+    serde_json::to_value(value).map_err(|err| RpcError::new(APPLICATION_ERROR, err.to_string()))
+}
+
+fn get_window(context: &Context, params: &JsonValue) -> Result<Rc<Window>, RpcError> {
+    let handle = WindowHandle(param(params, "window")?);
+    let window = context.window_manager.borrow().get_window(handle);
+    window.ok_or_else(|| Error::InvalidWindowHandle(handle).into())
+}
+
+fn handle_request(context: &Context, method: &str, params: JsonValue) -> RpcResult {
+    match method {
+        "listWindows" => to_json(&context.window_manager.borrow().window_infos()),
+        "getGeometry" => to_json(&get_window(context, &params)?.get_geometry()?),
+        "invokeMenuAction" => {
+            let menu = MenuHandle(param(&params, "menu")?);
+            let id: i64 = param(&params, "id")?;
+            let action = {
+                let menu_manager = context.menu_manager.borrow();
+                menu_manager.get_platform_menu(menu)?;
+                menu_manager.menu_action(menu, id)
+            };
+            if let Some(action) = action {
+                action();
+            }
+            Ok(JsonValue::Null)
+        }
+        "performDrop" => {
+            let window = get_window(context, &params)?;
+            let info: DraggingInfo = serde_json::from_value(params)
+                .map_err(|err| RpcError::new(INVALID_PARAMS, err.to_string()))?;
+            window.perform_drop(&info);
+            Ok(JsonValue::Null)
+        }
+        "requestClose" => {
+            get_window(context, &params)?.did_request_close();
+            Ok(JsonValue::Null)
+        }
+        "diagnostics" => to_json(&context.diagnostics()),
+        "channelStats" => to_json(&context.channel_metrics.snapshot()),
+        _ => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown method '{}'", method),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, os::unix::net::UnixListener, path::PathBuf, process};
+
+    use serde_json::{json, Value as JsonValue};
+
+    use crate::{
+        error_code,
+        shell::{Context, RunLoopSender},
+    };
+
+    use super::{
+        handle_line, handle_request, remove_stale_socket, RpcResult, APPLICATION_ERROR,
+        INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND, PARSE_ERROR,
+    };
+
+    fn error(result: RpcResult) -> (i64, JsonValue) {
+        match result {
+            Ok(result) => panic!("expected error, got {}", result),
+            Err(error) => (error.code, error.data),
+        }
+    }
+
+    #[test]
+    fn test_handle_request() {
+        let context = Context::new(Default::default()).unwrap();
+        assert_eq!(
+            handle_request(&context, "listWindows", JsonValue::Null).ok(),
+            Some(json!([]))
+        );
+        assert_eq!(
+            error(handle_request(&context, "unknown", JsonValue::Null)).0,
+            METHOD_NOT_FOUND
+        );
+        assert_eq!(
+            error(handle_request(&context, "getGeometry", json!({}))).0,
+            INVALID_PARAMS
+        );
+        assert_eq!(
+            error(handle_request(
+                &context,
+                "getGeometry",
+                json!({ "window": 10 })
+            )),
+            (
+                APPLICATION_ERROR,
+                json!({ "code": error_code::INVALID_WINDOW_HANDLE })
+            )
+        );
+        assert_eq!(
+            error(handle_request(
+                &context,
+                "invokeMenuAction",
+                json!({ "menu": 10, "id": 1 })
+            )),
+            (
+                APPLICATION_ERROR,
+                json!({ "code": error_code::INVALID_MENU_HANDLE })
+            )
+        );
+    }
+
+    #[test]
+    fn test_handle_line() {
+        let context = Context::new(Default::default()).unwrap();
+        let sender: RunLoopSender = context.run_loop.borrow().new_sender();
+        let error_code = |response: JsonValue| response["error"]["code"].as_i64();
+
+        let response = handle_line("{", &sender);
+        assert_eq!(response["id"], JsonValue::Null);
+        assert_eq!(error_code(response), Some(PARSE_ERROR));
+
+        let response = handle_line(r#"{"jsonrpc": "2.0", "id": 5}"#, &sender);
+        assert_eq!(response["id"], json!(5));
+        assert_eq!(error_code(response), Some(INVALID_REQUEST));
+    }
+
+    fn socket_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("nanoshell-{}-{}.sock", name, process::id()))
+    }
+
+    #[test]
+    fn test_remove_stale_socket() {
+        // nothing to remove
+        let path = socket_path("missing");
+        remove_stale_socket(&path).unwrap();
+
+        // regular file is kept
+        let path = socket_path("file");
+        fs::write(&path, "data").unwrap();
+        assert!(remove_stale_socket(&path).is_err());
+        assert!(path.exists());
+        fs::remove_file(&path).unwrap();
+
+        // socket someone is listening on is kept
+        let path = socket_path("live");
+        let _ = fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        assert!(remove_stale_socket(&path).is_err());
+        assert!(path.exists());
+
+        // left behind by previous run
+        drop(listener);
+        remove_stale_socket(&path).unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -1,5 +1,20 @@
-use super::platform::binary_messenger::PlatformBinaryMessenger;
-use crate::Result;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+    time::Duration,
+};
+
+use super::{
+    call_options::CancellationRegistration,
+    message_middleware::MiddlewareChain,
+    platform::{binary_messenger::PlatformBinaryMessenger, error::PlatformResult},
+    CallOptions, ChannelMetrics, MessageDirection, MessageForwarder, MessageMiddleware,
+    MiddlewareHandle, RunLoop, ScheduledCallback,
+};
+use crate::{
+    util::{trace, DispatchScope, OkLog},
+    Error, Result,
+};
 
 pub struct BinaryMessengerReply {
     sent: bool,
@@ -25,21 +40,205 @@ impl BinaryMessengerReply {
 }
 
 pub struct BinaryMessenger {
-    messenger: PlatformBinaryMessenger,
+    messenger: Rc<PlatformBinaryMessenger>,
+    run_loop: RunLoop,
+    middlewares: Rc<RefCell<Vec<MiddlewareEntry>>>,
+    next_middleware: Cell<usize>,
+    dispatch_on_run_loop: Rc<Cell<bool>>,
+    metrics: ChannelMetrics,
+}
+
+struct MiddlewareEntry {
+    handle: MiddlewareHandle,
+    channel: Option<String>,
+    middleware: Rc<dyn MessageMiddleware>,
+}
+
+type IncomingHandler = Rc<dyn Fn(&[u8], BinaryMessengerReply)>;
+
+type PendingReplyCallback = Box<dyn FnOnce(Result<&[u8]>)>;
+
+// Reply callback for send_message_with_options; whichever comes first
+// (reply, timeout, cancellation) takes the callback.
+struct PendingReply {
+    callback: Option<PendingReplyCallback>,
+    timeout: Option<ScheduledCallback>,
+    cancellation: Option<CancellationRegistration>,
+}
+
+impl PendingReply {
+    fn take(&mut self) -> Option<PendingReplyCallback> {
+        self.timeout.take();
+        self.cancellation.take();
+        self.callback.take()
+    }
+
+    fn resolve(pending: &RefCell<PendingReply>, result: Result<&[u8]>) {
+        let callback = pending.borrow_mut().take();
+        if let Some(callback) = callback {
+            callback(result);
+        }
+    }
 }
 
 impl BinaryMessenger {
-    pub fn new(messenger_impl: PlatformBinaryMessenger) -> Self {
+    pub fn new(
+        messenger_impl: PlatformBinaryMessenger,
+        run_loop: RunLoop,
+        metrics: ChannelMetrics,
+    ) -> Self {
         BinaryMessenger {
-            messenger: messenger_impl,
+            messenger: Rc::new(messenger_impl),
+            run_loop,
+            middlewares: Rc::new(RefCell::new(Vec::new())),
+            next_middleware: Cell::new(1),
+            dispatch_on_run_loop: Rc::new(Cell::new(false)),
+            metrics,
         }
     }
 
+    #[cfg(test)]
+    pub(crate) fn platform_messenger(&self) -> &PlatformBinaryMessenger {
+        &self.messenger
+    }
+
+    // Counters for traffic on this messenger; Shared by all engines of a context
+    pub fn metrics(&self) -> &ChannelMetrics {
+        &self.metrics
+    }
+
+    // When enabled, incoming messages are handled on next run loop turn rather
+    // than directly in engine callback.
+    pub fn set_dispatch_on_run_loop(&self, dispatch_on_run_loop: bool) {
+        self.dispatch_on_run_loop.set(dispatch_on_run_loop);
+    }
+
+    // Installs middleware for given channel, or for all channels if channel is None.
+    // Messages go through middlewares in order in which they were added, replies
+    // in reverse order.
+    pub fn add_middleware<M>(&self, channel: Option<&str>, middleware: M) -> MiddlewareHandle
+    where
+        M: MessageMiddleware + 'static,
+    {
+        let handle = MiddlewareHandle(self.next_middleware.get());
+        self.next_middleware.set(handle.0 + 1);
+        self.middlewares.borrow_mut().push(MiddlewareEntry {
+            handle,
+            channel: channel.map(|c| c.into()),
+            middleware: Rc::new(middleware),
+        });
+        handle
+    }
+
+    pub fn remove_middleware(&self, handle: MiddlewareHandle) {
+        self.middlewares
+            .borrow_mut()
+            .retain(|entry| entry.handle != handle);
+    }
+
+    fn middleware_chain(
+        middlewares: &RefCell<Vec<MiddlewareEntry>>,
+        channel: &str,
+    ) -> (MiddlewareChain, MiddlewareChain) {
+        let chain: Vec<_> = middlewares
+            .borrow()
+            .iter()
+            .filter(|entry| entry.channel.is_none() || entry.channel.as_deref() == Some(channel))
+            .map(|entry| entry.middleware.clone())
+            .collect();
+        let reply_chain = chain.iter().rev().cloned().collect();
+        (Rc::new(chain), Rc::new(reply_chain))
+    }
+
     pub fn register_channel_handler<F>(&self, channel: &str, callback: F)
     where
         F: Fn(&[u8], BinaryMessengerReply) -> () + 'static,
     {
-        self.messenger.register_channel_handler(channel, callback);
+        let channel_name: String = channel.into();
+        let channel_copy = channel_name.clone();
+        let callback: IncomingHandler = Rc::new(move |data, reply| {
+            let span = trace::channel_handler_span(&channel_copy);
+            let _guard = span.enter();
+            let _scope = DispatchScope::enter(&channel_copy);
+            callback(data, reply);
+        });
+        let middlewares = self.middlewares.clone();
+        let dispatch_on_run_loop = self.dispatch_on_run_loop.clone();
+        let run_loop = self.run_loop.clone();
+        let metrics = self.metrics.clone();
+        self.messenger
+            .register_channel_handler(channel, move |data, reply| {
+                metrics.record_received(&channel_name, data.len());
+                let reply = {
+                    let metrics = metrics.clone();
+                    let channel_name = channel_name.clone();
+                    BinaryMessengerReply::new(move |reply_data| {
+                        metrics.record_reply_sent(&channel_name, reply_data.len());
+                        reply.send(reply_data);
+                    })
+                };
+                if dispatch_on_run_loop.get() {
+                    let data: Vec<u8> = data.into();
+                    let middlewares = middlewares.clone();
+                    let channel_name = channel_name.clone();
+                    let callback = callback.clone();
+                    run_loop
+                        .schedule(
+                            move || {
+                                Self::dispatch_incoming(
+                                    &middlewares,
+                                    &channel_name,
+                                    callback,
+                                    &data,
+                                    reply,
+                                );
+                            },
+                            Duration::from_secs(0),
+                        )
+                        .detach();
+                } else {
+                    Self::dispatch_incoming(
+                        &middlewares,
+                        &channel_name,
+                        callback.clone(),
+                        data,
+                        reply,
+                    );
+                }
+            });
+    }
+
+    fn dispatch_incoming(
+        middlewares: &RefCell<Vec<MiddlewareEntry>>,
+        channel_name: &str,
+        callback: IncomingHandler,
+        data: &[u8],
+        reply: BinaryMessengerReply,
+    ) {
+        let (chain, reply_chain) = Self::middleware_chain(middlewares, channel_name);
+        if chain.is_empty() {
+            callback(data, reply);
+            return;
+        }
+        let channel_name: String = channel_name.into();
+        MessageForwarder::dispatch_message(
+            chain,
+            &channel_name.clone(),
+            MessageDirection::Incoming,
+            data.into(),
+            move |data| {
+                let reply = BinaryMessengerReply::new(move |reply_data| {
+                    MessageForwarder::dispatch_reply(
+                        reply_chain,
+                        &channel_name,
+                        MessageDirection::Incoming,
+                        reply_data.into(),
+                        move |reply_data| reply.send(&reply_data),
+                    );
+                });
+                callback(&data, reply);
+            },
+        );
     }
 
     pub fn unregister_channel_handler(&self, channel: &str) {
@@ -50,16 +249,173 @@ impl BinaryMessenger {
     where
         F: FnOnce(&[u8]) -> () + 'static,
     {
-        self.messenger
-            .send_message(channel, message, reply_callback)
-            .map_err(|e| e.into())
+        self.metrics.record_sent(channel, message.len());
+        let metrics = self.metrics.clone();
+        let channel_name: String = channel.into();
+        let res = self.send_message_unrecorded(channel, message, move |reply| {
+            metrics.record_reply_received(&channel_name, reply.len());
+            reply_callback(reply);
+        });
+        if res.is_err() {
+            self.metrics.record_error(channel);
+        }
+        res
+    }
+
+    fn send_message_unrecorded<F>(
+        &self,
+        channel: &str,
+        message: &[u8],
+        reply_callback: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(&[u8]) + 'static,
+    {
+        let (chain, reply_chain) = Self::middleware_chain(&self.middlewares, channel);
+        if chain.is_empty() {
+            return self
+                .messenger
+                .send_message(channel, message, reply_callback)
+                .map_err(|e| e.into());
+        }
+        let channel_name: String = channel.into();
+        self.forward_outgoing(chain, channel, message, move |messenger, channel, data| {
+            messenger.send_message(channel, data, move |reply_data| {
+                MessageForwarder::dispatch_reply(
+                    reply_chain,
+                    &channel_name,
+                    MessageDirection::Outgoing,
+                    reply_data.into(),
+                    move |reply_data| reply_callback(&reply_data),
+                );
+            })
+        })
+    }
+
+    // Passes outgoing message through middleware chain; Returns send result if
+    // the message was forwarded synchronously, Ok(()) if it was delayed or swallowed.
+    // Errors sending delayed message are logged.
+    fn forward_outgoing<F>(
+        &self,
+        chain: MiddlewareChain,
+        channel: &str,
+        message: &[u8],
+        send: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(&PlatformBinaryMessenger, &str, &[u8]) -> PlatformResult<()> + 'static,
+    {
+        let result = Rc::new(RefCell::new(None::<Result<()>>));
+        let dispatched = Rc::new(Cell::new(false));
+        let messenger = Rc::downgrade(&self.messenger);
+        let channel_name: String = channel.into();
+        let result_copy = result.clone();
+        let dispatched_copy = dispatched.clone();
+        MessageForwarder::dispatch_message(
+            chain,
+            channel,
+            MessageDirection::Outgoing,
+            message.into(),
+            move |data| {
+                let res = match messenger.upgrade() {
+                    Some(messenger) => send(&messenger, &channel_name, &data).map_err(|e| e.into()),
+                    None => Err(Error::EngineShutDown),
+                };
+                if dispatched_copy.get() {
+                    res.ok_log();
+                } else {
+                    result_copy.replace(Some(res));
+                }
+            },
+        );
+        dispatched.set(true);
+        let res = result.borrow_mut().take();
+        res.unwrap_or(Ok(()))
+    }
+
+    // Like "send_message", but the reply can time out (resolving with
+    // Error::Timeout) or be cancelled (callback is dropped without being called).
+    pub fn send_message_with_options<F>(
+        &self,
+        channel: &str,
+        message: &[u8],
+        options: CallOptions,
+        reply_callback: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(Result<&[u8]>) + 'static,
+    {
+        if let Some(token) = &options.cancellation_token {
+            if token.is_cancelled() {
+                return Ok(());
+            }
+        }
+
+        let pending = Rc::new(RefCell::new(PendingReply {
+            callback: Some(Box::new(reply_callback)),
+            timeout: None,
+            cancellation: None,
+        }));
+
+        if let Some(timeout) = options.timeout {
+            let pending_weak = Rc::downgrade(&pending);
+            let metrics = self.metrics.clone();
+            let channel_name: String = channel.into();
+            let timeout = self.run_loop.schedule(
+                move || {
+                    if let Some(pending) = pending_weak.upgrade() {
+                        metrics.record_error(&channel_name);
+                        PendingReply::resolve(&pending, Err(Error::Timeout));
+                    }
+                },
+                timeout,
+            );
+            pending.borrow_mut().timeout.replace(timeout);
+        }
+
+        if let Some(token) = &options.cancellation_token {
+            let pending_weak = Rc::downgrade(&pending);
+            let registration = token.register(move || {
+                if let Some(pending) = pending_weak.upgrade() {
+                    let callback = pending.borrow_mut().take();
+                    drop(callback);
+                }
+            });
+            pending.borrow_mut().cancellation.replace(registration);
+        }
+
+        let pending_copy = pending.clone();
+        let res = self.send_message(channel, message, move |reply| {
+            PendingReply::resolve(&pending_copy, Ok(reply));
+        });
+        if res.is_err() {
+            // not sent, make sure the timeout doesn't fire
+            pending.borrow_mut().take();
+        }
+        res
     }
 
     // like "send_message" but wihtout reply
     pub fn post_message(&self, channel: &str, message: &[u8]) -> Result<()> {
-        self.messenger
-            .post_message(channel, message)
-            .map_err(|e| e.into())
+        self.metrics.record_sent(channel, message.len());
+        let res = self.post_message_unrecorded(channel, message);
+        if res.is_err() {
+            self.metrics.record_error(channel);
+        }
+        res
+    }
+
+    fn post_message_unrecorded(&self, channel: &str, message: &[u8]) -> Result<()> {
+        let (chain, _) = Self::middleware_chain(&self.middlewares, channel);
+        if chain.is_empty() {
+            return self
+                .messenger
+                .post_message(channel, message)
+                .map_err(|e| e.into());
+        }
+        self.forward_outgoing(chain, channel, message, |messenger, channel, data| {
+            messenger.post_message(channel, data)
+        })
     }
 }
 
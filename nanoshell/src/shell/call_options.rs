@@ -0,0 +1,90 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::{Rc, Weak},
+    time::Duration,
+};
+
+// Options for outgoing calls (BinaryMessenger::send_message_with_options,
+// MethodInvoker::call_method_with_options, WindowMethodInvoker::call_method_with_options).
+// Regardless of options the reply callback is invoked at most once.
+#[derive(Clone, Default)]
+pub struct CallOptions {
+    // When set, the call resolves with Error::Timeout if no reply arrives in time
+    pub timeout: Option<Duration>,
+
+    // When cancelled, pending reply callbacks are dropped without being invoked
+    pub cancellation_token: Option<CancellationToken>,
+}
+
+impl CallOptions {
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            cancellation_token: None,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    state: Rc<CancellationState>,
+}
+
+#[derive(Default)]
+struct CancellationState {
+    cancelled: Cell<bool>,
+    next_id: Cell<usize>,
+    pending: RefCell<HashMap<usize, Box<dyn FnOnce()>>>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.get()
+    }
+
+    // Drops callbacks of all calls pending on this token; Calls made with
+    // already cancelled token are not sent at all.
+    pub fn cancel(&self) {
+        self.state.cancelled.set(true);
+        let pending: Vec<_> = self.state.pending.borrow_mut().drain().collect();
+        for (_, on_cancel) in pending {
+            on_cancel();
+        }
+    }
+
+    pub(super) fn register<F>(&self, on_cancel: F) -> CancellationRegistration
+    where
+        F: FnOnce() + 'static,
+    {
+        let id = self.state.next_id.get();
+        self.state.next_id.set(id + 1);
+        self.state
+            .pending
+            .borrow_mut()
+            .insert(id, Box::new(on_cancel));
+        CancellationRegistration {
+            state: Rc::downgrade(&self.state),
+            id,
+        }
+    }
+}
+
+// Removes the cancellation callback when dropped (i.e. after call completes)
+pub(super) struct CancellationRegistration {
+    state: Weak<CancellationState>,
+    id: usize,
+}
+
+impl Drop for CancellationRegistration {
+    fn drop(&mut self) {
+        if let Some(state) = self.state.upgrade() {
+            let on_cancel = state.pending.borrow_mut().remove(&self.id);
+            drop(on_cancel);
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelStats {
+    // Messages received from Dart and sent to Dart (replies are not counted)
+    pub messages_received: u64,
+    pub messages_sent: u64,
+
+    // Payload of messages and replies in each direction
+    pub bytes_received: u64,
+    pub bytes_sent: u64,
+
+    // Failed sends, timeouts, malformed messages and error replies
+    pub errors: u64,
+}
+
+// Per channel counters aggregated over all engines; Cloned instance refers to
+// the same counters.
+#[derive(Clone, Default)]
+pub struct ChannelMetrics {
+    stats: Rc<RefCell<HashMap<String, ChannelStats>>>,
+}
+
+impl ChannelMetrics {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn get(&self, channel: &str) -> Option<ChannelStats> {
+        self.stats.borrow().get(channel).cloned()
+    }
+
+    pub fn snapshot(&self) -> HashMap<String, ChannelStats> {
+        self.stats.borrow().clone()
+    }
+
+    pub fn reset(&self) {
+        self.stats.borrow_mut().clear();
+    }
+
+    fn update<F>(&self, channel: &str, f: F)
+    where
+        F: FnOnce(&mut ChannelStats),
+    {
+        let mut stats = self.stats.borrow_mut();
+        match stats.get_mut(channel) {
+            Some(entry) => f(entry),
+            None => f(stats.entry(channel.into()).or_default()),
+        }
+    }
+
+    pub(crate) fn record_received(&self, channel: &str, bytes: usize) {
+        self.update(channel, |s| {
+            s.messages_received += 1;
+            s.bytes_received += bytes as u64;
+        });
+    }
+
+    pub(crate) fn record_sent(&self, channel: &str, bytes: usize) {
+        self.update(channel, |s| {
+            s.messages_sent += 1;
+            s.bytes_sent += bytes as u64;
+        });
+    }
+
+    pub(crate) fn record_reply_received(&self, channel: &str, bytes: usize) {
+        self.update(channel, |s| s.bytes_received += bytes as u64);
+    }
+
+    pub(crate) fn record_reply_sent(&self, channel: &str, bytes: usize) {
+        self.update(channel, |s| s.bytes_sent += bytes as u64);
+    }
+
+    pub(crate) fn record_error(&self, channel: &str) {
+        self.update(channel, |s| s.errors += 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        codec::{StandardMethodCodec, Value},
+        shell::Context,
+    };
+
+    use super::{ChannelMetrics, ChannelStats};
+
+    #[test]
+    fn test_record() {
+        let metrics = ChannelMetrics::new();
+        assert_eq!(metrics.get("channel"), None);
+
+        metrics.record_received("channel", 10);
+        metrics.record_reply_sent("channel", 2);
+        metrics.record_sent("channel", 5);
+        metrics.record_reply_received("channel", 3);
+        metrics.record_error("channel");
+        metrics.record_sent("other", 1);
+        assert_eq!(
+            metrics.get("channel"),
+            Some(ChannelStats {
+                messages_received: 1,
+                messages_sent: 1,
+                bytes_received: 13,
+                bytes_sent: 7,
+                errors: 1,
+            })
+        );
+
+        // clones share counters
+        let copy = metrics.clone();
+        copy.record_error("other");
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot["other"].errors, 1);
+
+        copy.reset();
+        assert!(metrics.snapshot().is_empty());
+    }
+
+    #[test]
+    fn test_method_call() {
+        let context = Context::new(Default::default()).unwrap();
+        let engine = context.engine_manager.borrow_mut().create_engine();
+        let mut message_manager = context.message_manager.borrow_mut();
+        message_manager.register_method_handler("channel", &StandardMethodCodec, |_, _, _| {});
+        let invoker = message_manager
+            .get_method_invoker(engine, "channel")
+            .unwrap();
+        drop(message_manager);
+        context
+            .engine_manager
+            .borrow()
+            .get_engine(engine)
+            .unwrap()
+            .binary_messenger()
+            .platform_messenger()
+            .set_engine_handler("channel", |_, reply| reply.send(&[200]));
+
+        // malformed reply is counted as error
+        invoker
+            .call_method("method".into(), Value::Null, |_| {})
+            .unwrap();
+        let stats = context.channel_metrics.get("channel").unwrap();
+        assert_eq!(stats.messages_sent, 1);
+        assert!(stats.bytes_sent > 0);
+        assert_eq!(stats.bytes_received, 1);
+        assert_eq!(stats.errors, 1);
+    }
+}
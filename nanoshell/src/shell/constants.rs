@@ -12,6 +12,22 @@ pub(crate) mod channel {
 
     // Flutter channel for mananing platform menus
     pub const MENU_MANAGER: &str = "nanoshell/menu-manager";
+
+    // Flutter channel for querying diagnostics snapshot
+    pub const DIAGNOSTICS: &str = "nanoshell/diagnostics";
+
+    // Flutter channel for querying displays and receiving display changes
+    pub const DISPLAY_MANAGER: &str = "nanoshell/display-manager";
+
+    // Key events intercepted by Dart are sent on this channel; Reply contains the
+    // event with additional platform specific fields
+    pub const KEY_EVENT: &str = "nanoshell/keyevent";
+}
+
+// Version of the window dispatcher protocol; Must be incremented on every incompatible
+// change and kept in sync with Protocol.version in lib/src/constants.dart
+pub(crate) mod protocol {
+    pub const VERSION: i64 = 1;
 }
 
 pub(crate) mod method {
@@ -47,6 +63,15 @@ pub(crate) mod method {
         pub const SUPPORTED_GEOMETRY: &str = "method:window:supported-geometry";
 
         pub const SET_STYLE: &str = "method:window:set-style";
+        pub const SET_TITLE: &str = "method:window:set-title";
+
+        // Normal, minimized, maximized or full screen
+        pub const SET_STATE: &str = "method:window:set-state";
+        pub const GET_STATE: &str = "method:window:get-state";
+
+        // Bring the window to front and give it keyboard focus
+        pub const ACTIVATE: &str = "method:window:activate";
+
         pub const PERFORM_WINDOW_DRAG: &str = "method:window:perform-window-drag";
 
         pub const SHOW_POPUP_MENU: &str = "method:window:show-popup-menu";
@@ -78,6 +103,16 @@ pub(crate) mod method {
         pub const SET_APP_MENU: &str = "method:menu:set-app-menu";
     }
 
+    pub mod diagnostics {
+        // Returns snapshot of current application state (Diagnostics)
+        pub const GET: &str = "method:diagnostics:get";
+    }
+
+    pub mod displays {
+        // Returns list of displays (Display) in logical coordinates
+        pub const GET: &str = "method:displays:get";
+    }
+
     pub mod menu_bar {
         // Menubar - move to previous menu
         pub const MOVE_TO_PREVIOUS_MENU: &str = "method:menubar:move-to-previous-menu";
@@ -99,7 +134,21 @@ pub(crate) mod event {
 
         // Delivered when window is actually closed
         pub const CLOSE: &str = "event:window:close";
+
+        // Called when window was minimized, maximized, entered full screen or restored
+        pub const STATE_CHANGED: &str = "event:window:state-changed";
+
+        // Called when window became active (key) or inactive (boolean argument)
+        pub const ACTIVATION_CHANGED: &str = "event:window:activation-changed";
+
+        // Called when window was moved or resized (WindowGeometry argument); During
+        // live resize the updates are coalesced
+        pub const GEOMETRY_CHANGED: &str = "event:window:geometry-changed";
     }
+
+    // Delivered on display manager channel to all engines when displays were added,
+    // removed or reconfigured (list of displays as argument)
+    pub const DISPLAYS_CHANGED: &str = "event:displays-changed";
 }
 
 pub(crate) mod drag_data {
@@ -1,10 +1,12 @@
-use std::rc::Rc;
+use std::{path::PathBuf, rc::Rc};
 
 use crate::{util::LateRefCell, Result};
 
 use super::{
+    diagnostics::register_diagnostics_channel,
     platform::{drag_data::DragDataAdapter, init::init_platform},
-    EngineManager, MenuManager, MessageManager, RunLoop, WindowManager, WindowMethodChannel,
+    ChannelMetrics, DisplayManager, EngineManager, MenuManager, MessageManager, RunLoop,
+    WindowManager, WindowMethodChannel, WindowStateStore,
 };
 
 pub struct ContextOptions {
@@ -12,6 +14,15 @@ pub struct ContextOptions {
 
     pub on_last_engine_removed: Box<dyn Fn(Rc<Context>) -> ()>,
     pub custom_drag_data_adapters: Vec<Box<dyn DragDataAdapter>>,
+
+    // When true, incoming channel messages are dispatched on next run loop turn
+    // instead of directly from the engine callback; This allows handlers to
+    // safely remove engines and borrow managers.
+    pub dispatch_messages_on_run_loop: bool,
+
+    // When set, geometry and state of windows created with persistence key are
+    // saved to this file and restored on creation (see default_window_state_file)
+    pub window_state_file: Option<PathBuf>,
 }
 
 impl Default for ContextOptions {
@@ -20,6 +31,8 @@ impl Default for ContextOptions {
             app_namespace: Default::default(),
             on_last_engine_removed: Box::new(|context| context.run_loop.borrow().stop()),
             custom_drag_data_adapters: Vec::new(),
+            dispatch_messages_on_run_loop: false,
+            window_state_file: None,
         }
     }
 }
@@ -32,18 +45,26 @@ pub struct Context {
     pub window_method_channel: LateRefCell<WindowMethodChannel>,
     pub window_manager: LateRefCell<WindowManager>,
     pub menu_manager: LateRefCell<MenuManager>,
+    pub display_manager: LateRefCell<DisplayManager>,
+    pub window_state_store: LateRefCell<WindowStateStore>,
+
+    // Per channel message counters for all engines
+    pub channel_metrics: ChannelMetrics,
 }
 
 impl Context {
     pub fn new(options: ContextOptions) -> Result<Rc<Self>> {
         let res = Rc::new(Self {
             options,
-            run_loop: LateRefCell::new(),
-            engine_manager: LateRefCell::new(),
-            message_manager: LateRefCell::new(),
-            window_method_channel: LateRefCell::new(),
-            window_manager: LateRefCell::new(),
-            menu_manager: LateRefCell::new(),
+            run_loop: LateRefCell::named("run_loop"),
+            engine_manager: LateRefCell::named("engine_manager"),
+            message_manager: LateRefCell::named("message_manager"),
+            window_method_channel: LateRefCell::named("window_method_channel"),
+            window_manager: LateRefCell::named("window_manager"),
+            menu_manager: LateRefCell::named("menu_manager"),
+            display_manager: LateRefCell::named("display_manager"),
+            window_state_store: LateRefCell::named("window_state_store"),
+            channel_metrics: ChannelMetrics::new(),
         });
         res.initialize(res.clone())?;
         Ok(res)
@@ -58,6 +79,14 @@ impl Context {
             .set(WindowMethodChannel::new(context.clone()));
         self.window_manager.set(WindowManager::new(context.clone()));
         self.menu_manager.set(MenuManager::new(context.clone()));
+        self.display_manager
+            .set(DisplayManager::new(context.clone()));
+        self.window_state_store
+            .set(WindowStateStore::new(context.clone()));
+        register_diagnostics_channel(context.clone());
+
+        #[cfg(all(feature = "automation", unix))]
+        super::automation::start_from_env(&context);
 
         init_platform(context.clone()).map_err(|e| e.into())
     }
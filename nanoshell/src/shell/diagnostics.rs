@@ -0,0 +1,160 @@
+use std::rc::Rc;
+
+use crate::{
+    codec::{value::to_value, StandardMethodCodec, Value},
+    Result,
+};
+
+use super::{constants::*, Context, EngineHandle, EngineState, MenuHandle, WindowHandle};
+
+// Snapshot of application state; Serializable to Value (and JSON). Also available
+// to Dart through the diagnostics channel.
+#[derive(serde::Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostics {
+    pub engines: Vec<EngineInfo>,
+    pub windows: Vec<WindowInfo>,
+    pub message_handlers: Vec<HandlerInfo>,
+    pub method_handlers: Vec<HandlerInfo>,
+    pub window_method_handlers: Vec<String>,
+    pub menus: Vec<MenuInfo>,
+    pub pending_scheduled_callbacks: usize,
+}
+
+#[derive(serde::Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineInfo {
+    pub handle: EngineHandle,
+    pub state: EngineState,
+}
+
+#[derive(serde::Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowInfo {
+    pub handle: WindowHandle,
+    pub engine: EngineHandle,
+    pub parent: Option<WindowHandle>,
+    pub visible: bool,
+    pub active: bool,
+    pub initialized: bool,
+
+    // Messages waiting for window to be initialized
+    pub queued_messages: usize,
+}
+
+#[derive(serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum HandlerKind {
+    Exact,
+    Prefix,
+}
+
+#[derive(serde::Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HandlerInfo {
+    // Channel name or prefix, depending on kind
+    pub channel: String,
+
+    // None for handlers registered for all engines
+    pub engine: Option<EngineHandle>,
+    pub kind: HandlerKind,
+}
+
+#[derive(serde::Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MenuInfo {
+    pub handle: MenuHandle,
+    pub engine: EngineHandle,
+}
+
+impl Diagnostics {
+    pub fn to_value(&self) -> Result<Value> {
+        to_value(self).map_err(|e| e.into())
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+impl Context {
+    // Must not be called while any of the managers is mutably borrowed
+    pub fn diagnostics(&self) -> Diagnostics {
+        let message_manager = self.message_manager.borrow();
+        Diagnostics {
+            engines: self.engine_manager.borrow().engine_infos(),
+            windows: self.window_manager.borrow().window_infos(),
+            message_handlers: message_manager.message_handler_infos(),
+            method_handlers: message_manager.method_handler_infos(),
+            window_method_handlers: self.window_method_channel.borrow().registered_channels(),
+            menus: self.menu_manager.borrow().menu_infos(),
+            pending_scheduled_callbacks: self.run_loop.borrow().pending_callbacks(),
+        }
+    }
+}
+
+pub(super) fn register_diagnostics_channel(context: Rc<Context>) {
+    let context_copy = context.clone();
+    context
+        .message_manager
+        .borrow_mut()
+        .register_method_handler(
+            channel::DIAGNOSTICS,
+            &StandardMethodCodec,
+            move |call, reply, _engine| match call.method.as_str() {
+                method::diagnostics::GET => {
+                    reply.send(context_copy.diagnostics().to_value().map_err(|e| e.into()))
+                }
+                _ => reply.send_not_implemented(),
+            },
+        );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        codec::{StandardMethodCodec, Value},
+        shell::{Context, HandlerKind},
+    };
+
+    #[test]
+    fn test_diagnostics() {
+        let context = Context::new(Default::default()).unwrap();
+        let window = context
+            .window_manager
+            .borrow_mut()
+            .create_window(Value::Null, None);
+        context
+            .message_manager
+            .borrow_mut()
+            .register_message_handler("test-channel", &StandardMethodCodec, |_, _, _| {});
+
+        let diagnostics = context.diagnostics();
+        assert_eq!(diagnostics.windows.len(), 1);
+        let window_info = &diagnostics.windows[0];
+        assert_eq!(window_info.handle, window);
+        assert!(!window_info.initialized);
+        assert_eq!(diagnostics.engines.len(), 1);
+        assert_eq!(diagnostics.engines[0].handle, window_info.engine);
+        let handler = diagnostics
+            .message_handlers
+            .iter()
+            .find(|h| h.channel == "test-channel")
+            .unwrap();
+        assert_eq!(handler.engine, None);
+        assert_eq!(handler.kind, HandlerKind::Exact);
+        assert!(diagnostics.menus.is_empty());
+
+        let json: serde_json::Value =
+            serde_json::from_str(&diagnostics.to_json().unwrap()).unwrap();
+        assert_eq!(json["windows"][0]["handle"], window.0);
+        assert_eq!(json["windows"][0]["initialized"], false);
+        assert_eq!(
+            json["messageHandlers"].as_array().unwrap().len(),
+            diagnostics.message_handlers.len()
+        );
+
+        let value = diagnostics.to_value().unwrap();
+        assert!(matches!(value, Value::Map(map) if map.contains_key(&"engines".into())));
+    }
+}
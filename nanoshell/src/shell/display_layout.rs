@@ -0,0 +1,378 @@
+// Logical layout of displays with physical (pixel) coordinates. When displays have
+// different scale factors, scaled display rects are squeezed together so that there
+// are no gaps or overlaps between them in logical coordinates.
+
+use std::cmp::{self, Ordering};
+
+use super::{IPoint, IRect, Point, Rect};
+
+#[derive(Clone, Debug)]
+pub struct PhysicalDisplay {
+    pub physical: IRect,
+    pub scale: f64,
+
+    // Platform specific display identifier
+    pub id: i64,
+}
+
+#[derive(Clone, Debug)]
+pub struct LayoutDisplay {
+    pub physical: IRect,
+    pub logical: Rect,
+    pub scale: f64,
+    pub id: i64,
+}
+
+impl LayoutDisplay {
+    pub fn physical_to_logical(&self, rect: &IRect) -> Rect {
+        Rect::xywh(
+            self.logical.x + (rect.x - self.physical.x) as f64 / self.scale,
+            self.logical.y + (rect.y - self.physical.y) as f64 / self.scale,
+            rect.width as f64 / self.scale,
+            rect.height as f64 / self.scale,
+        )
+    }
+}
+
+pub struct DisplayLayout {
+    pub displays: Vec<LayoutDisplay>,
+}
+
+// Takes series of displays with physical bounds and calculates logical bounds for them
+impl DisplayLayout {
+    pub fn new(displays: Vec<PhysicalDisplay>) -> Self {
+        let mut w = Work::new(&displays);
+        w.perform();
+        Self {
+            displays: w
+                .state
+                .iter()
+                .map(|d| LayoutDisplay {
+                    physical: d.original.physical.clone(),
+                    logical: d.adjusted_logical.clone(),
+                    scale: d.original.scale,
+                    id: d.original.id,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn display_for_physical_point(&self, point: &IPoint) -> Option<&LayoutDisplay> {
+        self.displays
+            .iter()
+            .find(|d| d.physical.is_inside(point))
+            .or_else(|| {
+                self.displays.iter().min_by(|a, b| {
+                    a.physical
+                        .center()
+                        .distance(point)
+                        .partial_cmp(&b.physical.center().distance(point))
+                        .unwrap()
+                })
+            })
+    }
+
+    pub fn display_for_logical_point(&self, point: &Point) -> Option<&LayoutDisplay> {
+        self.displays
+            .iter()
+            .find(|d| d.logical.is_inside(point))
+            .or_else(|| {
+                self.displays.iter().min_by(|a, b| {
+                    a.logical
+                        .center()
+                        .distance(point)
+                        .partial_cmp(&b.logical.center().distance(point))
+                        .unwrap()
+                })
+            })
+    }
+
+    pub fn convert_physical_to_logical(&self, point: &IPoint) -> Option<Point> {
+        let display = self.display_for_physical_point(point);
+        match display {
+            Some(display) => {
+                let local = display.physical.to_local(point);
+                Some(Point::xy(
+                    local.x as f64 / display.scale + display.logical.x,
+                    local.y as f64 / display.scale + display.logical.y,
+                ))
+            }
+            None => None,
+        }
+    }
+
+    pub fn convert_logical_to_physical(&self, point: &Point) -> Option<IPoint> {
+        let display = self.display_for_logical_point(point);
+        match display {
+            Some(display) => {
+                let local = display.logical.to_local(point);
+                Some(IPoint::xy(
+                    (local.x * display.scale) as i32 + display.physical.x,
+                    (local.y * display.scale) as i32 + display.physical.y,
+                ))
+            }
+            None => None,
+        }
+    }
+}
+
+struct DisplayState {
+    original: PhysicalDisplay,
+    adjusted_physical: IRect,
+    adjusted_logical: Rect,
+}
+
+struct Work {
+    state: Vec<DisplayState>,
+}
+
+impl Work {
+    fn new(displays: &[PhysicalDisplay]) -> Self {
+        Work {
+            state: displays
+                .iter()
+                .map(|d| DisplayState {
+                    original: d.clone(),
+                    adjusted_physical: Default::default(),
+                    adjusted_logical: Default::default(),
+                })
+                .collect(),
+        }
+    }
+
+    // move physical displays so that minimum is at 0 0
+    fn adjust(&mut self) {
+        let mut min = (i32::MAX, i32::MAX);
+        for d in &mut self.state {
+            min.0 = cmp::min(min.0, d.original.physical.x);
+            min.1 = cmp::min(min.1, d.original.physical.y);
+        }
+        for d in &mut self.state {
+            d.adjusted_physical = IRect {
+                x: d.original.physical.x - min.0,
+                y: d.original.physical.y - min.1,
+                width: d.original.physical.width,
+                height: d.original.physical.height,
+            }
+        }
+    }
+
+    // sort physical displays
+    fn sort(&mut self) {
+        self.state.sort_by(|a, b| {
+            let res = a.adjusted_physical.x.cmp(&b.adjusted_physical.x);
+            match res {
+                Ordering::Equal => a.adjusted_physical.y.cmp(&b.adjusted_physical.y),
+                _ => res,
+            }
+        });
+    }
+
+    fn compute_initial_logical(&mut self) {
+        for d in &mut self.state {
+            d.adjusted_logical = Rect {
+                x: d.adjusted_physical.x as f64,
+                y: d.adjusted_physical.y as f64,
+                width: d.adjusted_physical.width as f64 / d.original.scale,
+                height: d.adjusted_physical.height as f64 / d.original.scale,
+            }
+        }
+    }
+
+    // remove gaps from adjusted_logical rects
+    fn squeeze(&mut self) {
+        'outer: loop {
+            for i in 0..self.state.len() {
+                let d = &self.state[i];
+
+                let min_x = self
+                    .state
+                    .iter()
+                    .filter(|d2| {
+                        d2.adjusted_logical.x < d.adjusted_logical.x
+                            && d2.adjusted_logical.x2() <= d.adjusted_logical.x2()
+                            // vertical intersection
+                            && d2.adjusted_logical.y < d.adjusted_logical.y2()
+                            && d2.adjusted_logical.y2() > d.adjusted_logical.y
+                    })
+                    .map(|d2| d2.adjusted_logical.x2())
+                    .max_by(|a, b| a.partial_cmp(b).unwrap())
+                    .unwrap_or(d.adjusted_logical.x);
+
+                let min_y = self
+                    .state
+                    .iter()
+                    .filter(|d2| {
+                        d2.adjusted_logical.y < d.adjusted_logical.y
+                            && d2.adjusted_logical.y2() <= d.adjusted_logical.y2()
+                            // horizontal intersection
+                            && d2.adjusted_logical.x < d.adjusted_logical.x2()
+                            && d2.adjusted_logical.x2() > d.adjusted_logical.x
+                    })
+                    .map(|d2| d2.adjusted_logical.y2())
+                    .max_by(|a, b| a.partial_cmp(b).unwrap())
+                    .unwrap_or(d.adjusted_logical.y);
+                if d.adjusted_logical.x != min_x || d.adjusted_logical.y != min_y {
+                    let d = &mut self.state[i];
+                    d.adjusted_logical.x = min_x;
+                    d.adjusted_logical.y = min_y;
+                    continue 'outer;
+                }
+            }
+            break;
+        }
+    }
+
+    // move adjusted logical rects so that main display starts at 0.0, 0.0
+    fn adjust_back(&mut self) {
+        let mut delta = (0.0, 0.0);
+        let main_display = self
+            .state
+            .iter()
+            .find(|d| d.original.physical.x == 0 && d.original.physical.y == 0);
+        if let Some(main_display) = main_display {
+            delta = (
+                main_display.adjusted_logical.x,
+                main_display.adjusted_logical.y,
+            )
+        }
+        for d in &mut self.state {
+            d.adjusted_logical.x -= delta.0;
+            d.adjusted_logical.y -= delta.1;
+        }
+    }
+
+    fn perform(&mut self) {
+        if self.state.is_empty() {
+            return;
+        }
+        let first = self.state.first().unwrap();
+        if self
+            .state
+            .iter()
+            .all(|d| d.original.scale == first.original.scale)
+        {
+            // all screens have same scaling factor, simply scale all rects
+            for d in &mut self.state {
+                d.adjusted_logical = Rect {
+                    x: d.original.physical.x as f64 / d.original.scale,
+                    y: d.original.physical.y as f64 / d.original.scale,
+                    width: d.original.physical.width as f64 / d.original.scale,
+                    height: d.original.physical.height as f64 / d.original.scale,
+                };
+            }
+        } else {
+            self.adjust();
+            self.sort();
+            self.compute_initial_logical();
+            self.squeeze();
+            self.adjust_back();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::shell::{IPoint, IRect, Point, Rect};
+
+    use super::{DisplayLayout, PhysicalDisplay};
+
+    #[test]
+    fn test1() {
+        let d = vec![
+            PhysicalDisplay {
+                physical: IRect::xywh(0, 0, 1920, 1080),
+                scale: 2.0,
+                id: 0,
+            },
+            PhysicalDisplay {
+                physical: IRect::xywh(1920, 0, 1920, 1080),
+                scale: 1.0,
+                id: 0,
+            },
+            PhysicalDisplay {
+                physical: IRect::xywh(-1920, 0, 1920, 1080),
+                scale: 2.0,
+                id: 0,
+            },
+            PhysicalDisplay {
+                physical: IRect::xywh(-(1920 + 1024), 0, 1024, 1024),
+                scale: 1.0,
+                id: 0,
+            },
+        ];
+
+        let displays = DisplayLayout::new(d);
+
+        assert_eq!(
+            displays.convert_physical_to_logical(&IPoint::xy(0, 0)),
+            Some(Point::xy(0.0, 0.0))
+        );
+
+        assert_eq!(
+            displays.convert_logical_to_physical(&Point::xy(0.0, 0.0)),
+            Some(IPoint::xy(0, 0))
+        );
+
+        assert_eq!(
+            displays.convert_physical_to_logical(&IPoint::xy(500, 500)),
+            Some(Point::xy(250.0, 250.0))
+        );
+
+        assert_eq!(
+            displays.convert_logical_to_physical(&Point::xy(250.0, 250.0)),
+            Some(IPoint::xy(500, 500))
+        );
+
+        assert_eq!(
+            displays.convert_physical_to_logical(&IPoint::xy(2000, 500)),
+            Some(Point::xy(1040.0, 500.0))
+        );
+
+        assert_eq!(
+            displays.convert_logical_to_physical(&Point::xy(1040.0, 500.0)),
+            Some(IPoint::xy(2000, 500))
+        );
+
+        let d = displays.display_for_physical_point(&IPoint::xy(-3500, 500));
+        assert_eq!(d.unwrap().physical.x, -2944);
+
+        // for r in &displays.displays {
+        //     println!(
+        //         "{} {} {} {}",
+        //         r.logical.x, r.logical.y, r.logical.width, r.logical.height
+        //     );
+        // }
+    }
+
+    #[test]
+    fn test2() {
+        let d = vec![
+            PhysicalDisplay {
+                physical: IRect::xywh(-3840, 14, 3840, 2160),
+                scale: 2.0,
+                id: 0,
+            },
+            PhysicalDisplay {
+                physical: IRect::xywh(0, 0, 1920, 1080),
+                scale: 1.25,
+                id: 0,
+            },
+        ];
+        let displays = DisplayLayout::new(d);
+        assert_eq!(
+            displays.displays[0].logical,
+            Rect::xywh(-1920.0, 14.0, 1920.0, 1080.0)
+        );
+        assert_eq!(
+            displays.displays[1].logical,
+            Rect::xywh(0.0, 0.0, 1536.0, 864.0)
+        );
+        // for r in displays.displays {
+        //     println!(
+        //         "{} {} {} {}",
+        //         r.logical.x, r.logical.y, r.logical.width, r.logical.height
+        //     );
+        // }
+    }
+}
@@ -0,0 +1,91 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    codec::{value::to_value, MethodCall, MethodCallReply, StandardMethodCodec, Value},
+    util::OkLog,
+};
+
+use super::{
+    constants::*, platform::display::PlatformDisplayManager, structs::Display, Context,
+    EngineHandle,
+};
+
+pub struct DisplayManager {
+    context: Rc<Context>,
+    platform_display_manager: PlatformDisplayManager,
+    last_displays: RefCell<Option<Vec<Display>>>,
+}
+
+impl DisplayManager {
+    pub(super) fn new(context: Rc<Context>) -> Self {
+        let context_copy = context.clone();
+        context
+            .message_manager
+            .borrow_mut()
+            .register_method_handler(
+                channel::DISPLAY_MANAGER,
+                &StandardMethodCodec,
+                move |call, reply, engine| {
+                    context_copy
+                        .display_manager
+                        .borrow()
+                        .on_method_call(call, reply, engine);
+                },
+            );
+
+        Self {
+            context: context.clone(),
+            platform_display_manager: PlatformDisplayManager::new(context),
+            last_displays: RefCell::new(None),
+        }
+    }
+
+    // Displays in logical coordinates with top left origin at primary display
+    pub fn get_displays(&self) -> Vec<Display> {
+        self.platform_display_manager.get_displays()
+    }
+
+    pub fn get_platform_display_manager(&self) -> &PlatformDisplayManager {
+        &self.platform_display_manager
+    }
+
+    // Called by platform when display configuration might have changed; Notifies
+    // all engines if the displays are actually different from last known state
+    pub(crate) fn displays_changed(&self) {
+        let displays = self.get_displays();
+        let last = self.last_displays.replace(Some(displays.clone()));
+        if last.as_ref() == Some(&displays) {
+            return;
+        }
+        let value = to_value(&displays).unwrap();
+        let engines = self.context.engine_manager.borrow().get_all_engines();
+        for engine in engines {
+            let invoker = self
+                .context
+                .message_manager
+                .borrow()
+                .get_method_invoker(engine, channel::DISPLAY_MANAGER);
+            if let Some(invoker) = invoker {
+                invoker
+                    .call_method(event::DISPLAYS_CHANGED.into(), value.clone(), |_| {})
+                    .ok_log();
+            }
+        }
+    }
+
+    fn on_method_call(
+        &self,
+        call: MethodCall<Value>,
+        reply: MethodCallReply<Value>,
+        _engine: EngineHandle,
+    ) {
+        match call.method.as_str() {
+            method::displays::GET => {
+                let displays = self.get_displays();
+                self.last_displays.replace(Some(displays.clone()));
+                reply.send_ok(to_value(&displays).unwrap());
+            }
+            _ => reply.send_not_implemented(),
+        }
+    }
+}
@@ -1,19 +1,40 @@
-use super::{platform::engine::PlatformEngine, BinaryMessenger};
+use super::{
+    constants::channel,
+    platform::{engine::PlatformEngine, key_event::process_key_event},
+    BinaryMessenger, ChannelMetrics, RunLoop,
+};
 use crate::Result;
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EngineState {
+    Created,
+    Launched,
+    LaunchFailed,
+}
+
 pub struct FlutterEngine {
     pub(super) platform_engine: PlatformEngine,
     binary_messenger: Option<BinaryMessenger>,
+    state: EngineState,
 }
 
 impl FlutterEngine {
-    pub fn create() -> Self {
+    pub fn create(run_loop: &RunLoop, metrics: &ChannelMetrics) -> Self {
         let platform_engine = PlatformEngine::new();
 
-        let messenger = BinaryMessenger::new(platform_engine.new_binary_messenger());
+        let messenger = BinaryMessenger::new(
+            platform_engine.new_binary_messenger(),
+            run_loop.clone(),
+            metrics.clone(),
+        );
+        messenger.register_channel_handler(channel::KEY_EVENT, |data, reply| {
+            reply.send(&process_key_event(data.into()));
+        });
         FlutterEngine {
             platform_engine,
             binary_messenger: Some(messenger),
+            state: EngineState::Created,
         }
     }
 
@@ -21,8 +42,17 @@ impl FlutterEngine {
         self.binary_messenger.as_ref().unwrap()
     }
 
+    pub fn state(&self) -> EngineState {
+        self.state
+    }
+
     pub fn launch(&mut self) -> Result<()> {
-        self.platform_engine.launch().map_err(|e| e.into())
+        let res = self.platform_engine.launch();
+        self.state = match res {
+            Ok(()) => EngineState::Launched,
+            Err(_) => EngineState::LaunchFailed,
+        };
+        res.map_err(|e| e.into())
     }
 
     pub fn shut_down(&mut self) -> Result<()> {
@@ -30,3 +60,46 @@ impl FlutterEngine {
         self.platform_engine.shut_down().map_err(|e| e.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::shell::{
+        constants::channel, Context, InterceptedMessage, MessageForwarder, MessageMiddleware,
+    };
+
+    struct SwallowKeyEvent;
+
+    impl MessageMiddleware for SwallowKeyEvent {
+        fn on_message(&self, _message: InterceptedMessage, _next: MessageForwarder) {}
+    }
+
+    #[test]
+    fn test_key_event() {
+        let context = Context::new(Default::default()).unwrap();
+        let engine = context.engine_manager.borrow_mut().create_engine();
+        let engine_manager = context.engine_manager.borrow();
+        let engine = engine_manager.get_engine(engine).unwrap();
+        let messenger = engine.binary_messenger();
+
+        let send_key_event = || {
+            let reply = Rc::new(RefCell::new(None));
+            let reply_copy = reply.clone();
+            let delivered = messenger.platform_messenger().deliver_message(
+                channel::KEY_EVENT,
+                b"{}",
+                move |data| {
+                    reply_copy.replace(Some(data.to_vec()));
+                },
+            );
+            assert!(delivered);
+            reply.take()
+        };
+        assert_eq!(send_key_event(), Some(b"{}".to_vec()));
+
+        // swallowed key event gets empty reply
+        messenger.add_middleware(Some(channel::KEY_EVENT), SwallowKeyEvent);
+        assert_eq!(send_key_event(), Some(Vec::new()));
+    }
+}
@@ -1,19 +1,63 @@
 use std::{
-    cell::{Ref, RefCell},
-    collections::HashMap,
-    rc::Rc,
+    cell::{Cell, Ref, RefCell},
+    collections::{BTreeMap, HashMap},
+    rc::{Rc, Weak},
+    time::Duration,
 };
 
-use super::{Context, FlutterEngine};
+use super::{
+    platform::error::PlatformError, reply_aggregator::ReplyAggregator, Context, EngineInfo,
+    FlutterEngine,
+};
 use crate::{Error, Result};
 
-#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, serde::Serialize)]
 pub struct EngineHandle(pub i64);
 
+#[derive(Debug, Clone)]
+pub enum EngineEvent {
+    Created(EngineHandle),
+    Launched(EngineHandle),
+    LaunchFailed(EngineHandle, PlatformError),
+    ShutDown(EngineHandle),
+}
+
+type EngineEventListener = dyn Fn(&EngineEvent);
+// Keyed by increasing id so that listeners are invoked in registration order
+type EngineEventListenerMap = RefCell<BTreeMap<usize, Rc<EngineEventListener>>>;
+
+// Keeps the listener registered for as long as the subscription is alive
+pub struct EngineEventSubscription {
+    listeners: Weak<EngineEventListenerMap>,
+    id: usize,
+}
+
+impl EngineEventSubscription {
+    pub fn cancel(&mut self) {
+        if let Some(listeners) = self.listeners.upgrade() {
+            listeners.borrow_mut().remove(&self.id);
+        }
+        self.listeners = Weak::new();
+    }
+
+    // Keeps the listener registered for the lifetime of engine manager
+    pub fn detach(&mut self) {
+        self.listeners = Weak::new();
+    }
+}
+
+impl Drop for EngineEventSubscription {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
 pub struct EngineManager {
     context: Rc<Context>,
     engines: HashMap<EngineHandle, Box<RefCell<FlutterEngine>>>,
     next_handle: EngineHandle,
+    listeners: Rc<EngineEventListenerMap>,
+    next_listener: Cell<usize>,
 }
 
 impl EngineManager {
@@ -22,11 +66,19 @@ impl EngineManager {
             context: context,
             engines: HashMap::new(),
             next_handle: EngineHandle(1),
+            listeners: Rc::new(RefCell::new(BTreeMap::new())),
+            next_listener: Cell::new(1),
         }
     }
 
     pub fn create_engine(&mut self) -> EngineHandle {
-        let engine = FlutterEngine::create();
+        let engine = FlutterEngine::create(
+            &self.context.run_loop.borrow(),
+            &self.context.channel_metrics,
+        );
+        engine
+            .binary_messenger()
+            .set_dispatch_on_run_loop(self.context.options.dispatch_messages_on_run_loop);
         let handle = self.next_handle;
         self.next_handle.0 += 1;
         self.engines.insert(handle, Box::new(RefCell::new(engine)));
@@ -34,15 +86,25 @@ impl EngineManager {
             .message_manager
             .borrow_mut()
             .engine_created(self, handle);
+        self.notify(EngineEvent::Created(handle));
         handle
     }
 
     pub fn launch_engine(&mut self, handle: EngineHandle) -> Result<()> {
-        self.engines
+        let res = self
+            .engines
             .get(&handle)
             .map(|engine| engine.borrow_mut().launch())
             .transpose()?
-            .ok_or(Error::InvalidEngineHandle)
+            .ok_or(Error::InvalidEngineHandle(handle));
+        match &res {
+            Ok(()) => self.notify(EngineEvent::Launched(handle)),
+            Err(Error::Platform(error)) => {
+                self.notify(EngineEvent::LaunchFailed(handle, error.clone()))
+            }
+            Err(_) => {}
+        }
+        res
     }
 
     pub fn get_engine(&self, handle: EngineHandle) -> Option<Ref<FlutterEngine>> {
@@ -53,7 +115,13 @@ impl EngineManager {
         let entry = self.engines.remove(&handle);
         if let Some(entry) = entry {
             let mut engine = entry.borrow_mut();
-            engine.shut_down()?;
+            let res = engine.shut_down();
+            self.context
+                .message_manager
+                .borrow_mut()
+                .engine_removed(handle);
+            self.notify(EngineEvent::ShutDown(handle));
+            res?;
         }
         if self.engines.is_empty() {
             (self.context.options.on_last_engine_removed)(self.context.clone());
@@ -65,6 +133,19 @@ impl EngineManager {
         self.engines.keys().map(|e| e.clone()).collect()
     }
 
+    pub(super) fn engine_infos(&self) -> Vec<EngineInfo> {
+        let mut res: Vec<EngineInfo> = self
+            .engines
+            .iter()
+            .map(|(handle, engine)| EngineInfo {
+                handle: *handle,
+                state: engine.borrow().state(),
+            })
+            .collect();
+        res.sort_by_key(|e| e.handle.0);
+        res
+    }
+
     // Posts message on all engines
     pub fn broadcast_message(&self, channel: &str, message: &[u8]) -> Result<()> {
         for (_handle, engine) in &self.engines {
@@ -75,4 +156,137 @@ impl EngineManager {
         }
         Ok(())
     }
+
+    // Sends message to all engines and invokes callback with replies from every engine
+    // once all engines replied; Engines that don't reply within timeout will have
+    // Error::Timeout as result.
+    pub fn broadcast_message_with_replies<F>(
+        &self,
+        channel: &str,
+        message: &[u8],
+        timeout: Duration,
+        callback: F,
+    ) where
+        F: FnOnce(HashMap<EngineHandle, Result<Vec<u8>>>) + 'static,
+    {
+        let aggregator = ReplyAggregator::new(
+            self.context.clone(),
+            self.get_all_engines(),
+            timeout,
+            |_| Err(Error::Timeout),
+            callback,
+        );
+        for (handle, engine) in &self.engines {
+            let handle = *handle;
+            let aggregator_copy = aggregator.clone();
+            let res =
+                engine
+                    .borrow()
+                    .binary_messenger()
+                    .send_message(channel, message, move |reply| {
+                        aggregator_copy.reply(handle, Ok(reply.into()))
+                    });
+            if let Err(error) = res {
+                aggregator.reply(handle, Err(error));
+            }
+        }
+    }
+
+    // Registers listener for engine lifecycle events; The listener stays registered
+    // until returned subscription is dropped.
+    #[must_use]
+    pub fn add_event_listener<F>(&self, listener: F) -> EngineEventSubscription
+    where
+        F: Fn(&EngineEvent) + 'static,
+    {
+        let id = self.next_listener.get();
+        self.next_listener.set(id + 1);
+        self.listeners.borrow_mut().insert(id, Rc::new(listener));
+        EngineEventSubscription {
+            listeners: Rc::downgrade(&self.listeners),
+            id,
+        }
+    }
+
+    // Listeners are invoked on next run loop turn; Events are usually emitted while
+    // engine manager (and often window manager) is mutably borrowed, so this leaves
+    // listeners free to use the managers (i.e. to open an error window). Only
+    // listeners registered at the time of event get it.
+    fn notify(&self, event: EngineEvent) {
+        let listeners: Vec<Rc<EngineEventListener>> =
+            self.listeners.borrow().values().cloned().collect();
+        if listeners.is_empty() {
+            return;
+        }
+        self.context
+            .run_loop
+            .borrow()
+            .schedule(
+                move || {
+                    for listener in listeners {
+                        listener(&event);
+                    }
+                },
+                Duration::from_secs(0),
+            )
+            .detach();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::shell::Context;
+
+    use super::{EngineEvent, EngineHandle};
+
+    #[test]
+    fn test_event_listeners() {
+        let context = Context::new(Default::default()).unwrap();
+        let events = Rc::new(RefCell::new(Vec::<(&str, EngineHandle)>::new()));
+        let listen = |name: &'static str| {
+            let events = events.clone();
+            context
+                .engine_manager
+                .borrow()
+                .add_event_listener(move |event| {
+                    if let EngineEvent::Created(handle) = event {
+                        events.borrow_mut().push((name, *handle));
+                    }
+                })
+        };
+        let first = listen("first");
+        let _second = listen("second");
+        let run_loop = context.run_loop.borrow().clone();
+
+        // delivered on run loop in registration order
+        let engine = context.engine_manager.borrow_mut().create_engine();
+        assert!(events.borrow().is_empty());
+        run_loop.run();
+        assert_eq!(
+            *events.borrow(),
+            vec![("first", engine), ("second", engine)]
+        );
+
+        // listeners registered after the event was emitted don't get it
+        events.borrow_mut().clear();
+        let engine = context.engine_manager.borrow_mut().create_engine();
+        let _third = listen("third");
+        run_loop.run();
+        assert_eq!(
+            *events.borrow(),
+            vec![("first", engine), ("second", engine)]
+        );
+
+        // dropping subscription unsubscribes
+        events.borrow_mut().clear();
+        drop(first);
+        let engine = context.engine_manager.borrow_mut().create_engine();
+        run_loop.run();
+        assert_eq!(
+            *events.borrow(),
+            vec![("second", engine), ("third", engine)]
+        );
+    }
 }
@@ -3,7 +3,7 @@ use std::{collections::HashMap, rc::Rc};
 use crate::{
     codec::{
         value::{from_value, to_value},
-        MethodCall, MethodCallReply, MethodInvoker, Value,
+        MethodCall, MethodCallError, MethodCallReply, MethodInvoker, StandardMethodCodec, Value,
     },
     util::OkLog,
     Error, Result,
@@ -13,7 +13,8 @@ use super::{
     constants::*,
     platform::menu::{PlatformMenu, PlatformMenuManager},
     structs::{MenuAction, MenuCreateRequest, MenuDestroyRequest, SetMenuRequest},
-    Context, EngineHandle, WindowMethodCallResult,
+    window_method_channel::invalid_request_error,
+    Context, EngineHandle, MenuInfo, WindowMethodCallResult,
 };
 
 struct MenuEntry {
@@ -37,12 +38,16 @@ impl MenuManager {
         context
             .message_manager
             .borrow_mut()
-            .register_method_handler(channel::MENU_MANAGER, move |value, reply, engine| {
-                context_copy
-                    .menu_manager
-                    .borrow_mut()
-                    .on_method_call(value, reply, engine);
-            });
+            .register_method_handler(
+                channel::MENU_MANAGER,
+                &StandardMethodCodec,
+                move |value, reply, engine| {
+                    context_copy
+                        .menu_manager
+                        .borrow_mut()
+                        .on_method_call(value, reply, engine);
+                },
+            );
 
         Self {
             context: context.clone(),
@@ -56,7 +61,20 @@ impl MenuManager {
         self.platform_menu_map
             .get(&menu)
             .map(|c| c.platform_menu.clone())
-            .ok_or(Error::InvalidMenuHandle)
+            .ok_or(Error::InvalidMenuHandle(menu))
+    }
+
+    pub(super) fn menu_infos(&self) -> Vec<MenuInfo> {
+        let mut res: Vec<MenuInfo> = self
+            .platform_menu_map
+            .iter()
+            .map(|(handle, entry)| MenuInfo {
+                handle: *handle,
+                engine: entry.engine,
+            })
+            .collect();
+        res.sort_by_key(|m| m.handle.0);
+        res
     }
 
     pub fn get_platform_menu_manager(&self) -> &PlatformMenuManager {
@@ -102,22 +120,33 @@ impl MenuManager {
         })
     }
 
+    #[allow(dead_code)] // only used on macos and windows
     pub(crate) fn on_menu_action(&self, menu_handle: MenuHandle, id: i64) {
-        if let Some(invoker) = self.invoker_for_menu(menu_handle) {
-            invoker
-                .call_method(
-                    method::menu::ON_ACTION.into(),
-                    to_value(&MenuAction {
-                        handle: menu_handle,
-                        id: id,
-                    })
-                    .unwrap(),
-                    |_| {},
-                )
-                .ok_log();
+        if let Some(action) = self.menu_action(menu_handle, id) {
+            action();
         }
     }
 
+    // Returns callback that dispatches the menu action; Lets the caller release
+    // menu manager borrow before the action is dispatched
+    pub(crate) fn menu_action(&self, menu_handle: MenuHandle, id: i64) -> Option<impl FnOnce()> {
+        self.invoker_for_menu(menu_handle).map(move |invoker| {
+            move || {
+                invoker
+                    .call_method(
+                        method::menu::ON_ACTION.into(),
+                        to_value(&MenuAction {
+                            handle: menu_handle,
+                            id: id,
+                        })
+                        .unwrap(),
+                        |_| {},
+                    )
+                    .ok_log();
+            }
+        })
+    }
+
     #[allow(dead_code)] // only used on windows
     pub(crate) fn move_to_previous_menu(&self, menu_handle: MenuHandle) {
         if let Some(invoker) = self.invoker_for_menu(menu_handle) {
@@ -151,6 +180,28 @@ impl MenuManager {
         result.map(|v| to_value(v).unwrap()).map_err(|e| e.into())
     }
 
+    fn send_result<T>(reply: MethodCallReply<Value>, result: Result<T>)
+    where
+        T: serde::Serialize,
+    {
+        match result {
+            Err(error) if error.is_not_implemented() => reply.send_not_implemented(),
+            result => reply.send(Self::map_result(result)),
+        }
+    }
+
+    fn decode_request<T>(call: &MethodCall<Value>) -> std::result::Result<T, MethodCallError<Value>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        from_value(&call.args).map_err(|error| {
+            invalid_request_error(
+                &format!("Invalid {} request: {}", call.method, error),
+                call.args.clone(),
+            )
+        })
+    }
+
     fn on_method_call(
         &mut self,
         call: MethodCall<Value>,
@@ -159,30 +210,41 @@ impl MenuManager {
     ) {
         match call.method.as_str() {
             method::menu::CREATE_OR_UPDATE => {
-                let request: MenuCreateRequest = from_value(&call.args).unwrap();
-                let res = self.on_create_or_update(request, engine);
-                reply.send(Self::map_result(res));
-            }
-            method::menu::DESTROY => {
-                let request: MenuDestroyRequest = from_value(&call.args).unwrap();
-                self.platform_menu_map.remove(&request.handle);
-                reply.send_ok(Value::Null);
-            }
-            method::menu::SET_APP_MENU => {
-                let request: SetMenuRequest = from_value(&call.args).unwrap();
-                let menu = self.platform_menu_map.get(&request.handle);
-                match menu {
-                    Some(menu) => reply.send(Self::map_result(
-                        self.platform_menu_manager
-                            .set_app_menu(menu.platform_menu.clone())
-                            .map_err(|e| e.into()),
-                    )),
-                    None => {
-                        reply.send(Self::map_result::<()>(Err(Error::InvalidMenuHandle)));
+                match Self::decode_request::<MenuCreateRequest>(&call) {
+                    Ok(request) => {
+                        let res = self.on_create_or_update(request, engine);
+                        Self::send_result(reply, res);
                     }
+                    Err(error) => reply.send(Err(error)),
                 }
             }
-            _ => {}
+            method::menu::DESTROY => match Self::decode_request::<MenuDestroyRequest>(&call) {
+                Ok(request) => {
+                    self.platform_menu_map.remove(&request.handle);
+                    reply.send_ok(Value::Null);
+                }
+                Err(error) => reply.send(Err(error)),
+            },
+            method::menu::SET_APP_MENU => match Self::decode_request::<SetMenuRequest>(&call) {
+                Ok(request) => {
+                    let menu = self.platform_menu_map.get(&request.handle);
+                    match menu {
+                        Some(menu) => Self::send_result(
+                            reply,
+                            self.platform_menu_manager
+                                .set_app_menu(menu.platform_menu.clone())
+                                .map_err(|e| e.into()),
+                        ),
+                        None => {
+                            reply.send(Self::map_result::<()>(Err(Error::InvalidMenuHandle(
+                                request.handle,
+                            ))));
+                        }
+                    }
+                }
+                Err(error) => reply.send(Err(error)),
+            },
+            _ => reply.send_not_implemented(),
         };
     }
 }
@@ -1,23 +1,135 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{cell::RefCell, collections::HashMap, rc::Rc, time::Duration};
 
-use crate::codec::{
-    MessageChannel, MessageReply, MessageSender, MethodCall, MethodCallReply, MethodChannel,
-    MethodInvoker, StandardMethodCodec, Value,
+use crate::{
+    codec::{
+        MessageChannel, MessageCodec, MessageReply, MessageSender, MethodCall, MethodCallReply,
+        MethodChannel, MethodCodec, MethodInvoker, Value,
+    },
+    Error, Result,
 };
 
-use super::{Context, EngineHandle, EngineManager};
+use super::{Context, EngineHandle, EngineManager, HandlerInfo, HandlerKind};
 
-type MessageCallback = dyn Fn(Value, MessageReply<Value>, EngineHandle) -> ();
-type MethodCallback = dyn Fn(MethodCall<Value>, MethodCallReply<Value>, EngineHandle) -> ();
+type MessageCallback = dyn Fn(Value, MessageReply<Value>, EngineHandle, &str);
+type MethodCallback = dyn Fn(MethodCall<Value>, MethodCallReply<Value>, EngineHandle, &str);
+
+// Routing table for channel handlers. Handlers can be registered for exact channel
+// name or for channel name prefix, either for all engines or for single engine.
+//
+// When looking up handler for incoming message the precedence is:
+// 1. exact channel name registered for the engine
+// 2. exact channel name registered for all engines
+// 3. longest prefix registered for the engine
+// 4. longest prefix registered for all engines
+//
+// Each exact handler and declared channel carries the codec used to register the
+// channel on engine.
+struct Routes<C: ?Sized, K: ?Sized + 'static> {
+    exact: HashMap<(Option<EngineHandle>, String), ExactRoute<C, K>>,
+    prefix: HashMap<(Option<EngineHandle>, String), Rc<C>>,
+
+    // Flutter only delivers messages on channels that have been registered with
+    // engine; These are channels without exact handler that are to be served by
+    // prefix handlers
+    declared: HashMap<String, &'static K>,
+}
+
+struct ExactRoute<C: ?Sized, K: ?Sized + 'static> {
+    handler: Rc<C>,
+    codec: &'static K,
+}
+
+impl<C: ?Sized, K: ?Sized + 'static> Routes<C, K> {
+    fn new() -> Self {
+        Self {
+            exact: HashMap::new(),
+            prefix: HashMap::new(),
+            declared: HashMap::new(),
+        }
+    }
+
+    fn find(&self, engine: EngineHandle, channel: &str) -> Option<Rc<C>> {
+        let channel = String::from(channel);
+        self.exact
+            .get(&(Some(engine), channel.clone()))
+            .or_else(|| self.exact.get(&(None, channel.clone())))
+            .map(|route| &route.handler)
+            .or_else(|| self.find_prefix(Some(engine), &channel))
+            .or_else(|| self.find_prefix(None, &channel))
+            .cloned()
+    }
+
+    fn find_prefix(&self, engine: Option<EngineHandle>, channel: &str) -> Option<&Rc<C>> {
+        self.prefix
+            .iter()
+            .filter(|((e, prefix), _)| *e == engine && channel.starts_with(prefix.as_str()))
+            .max_by_key(|((_, prefix), _)| prefix.len())
+            .map(|(_, handler)| handler)
+    }
+
+    fn remove_engine(&mut self, engine: EngineHandle) {
+        self.exact.retain(|(e, _), _| *e != Some(engine));
+        self.prefix.retain(|(e, _), _| *e != Some(engine));
+    }
+
+    fn handler_infos(&self) -> Vec<HandlerInfo> {
+        let exact = self.exact.keys().map(|(engine, channel)| HandlerInfo {
+            channel: channel.clone(),
+            engine: *engine,
+            kind: HandlerKind::Exact,
+        });
+        let prefix = self.prefix.keys().map(|(engine, prefix)| HandlerInfo {
+            channel: prefix.clone(),
+            engine: *engine,
+            kind: HandlerKind::Prefix,
+        });
+        let mut res: Vec<HandlerInfo> = exact.chain(prefix).collect();
+        res.sort_by(|a, b| a.channel.cmp(&b.channel));
+        res
+    }
+
+    // Channels (and their codecs) that need to be registered on given engine
+    fn channels_for_engine(&self, engine: EngineHandle) -> HashMap<String, &'static K> {
+        let mut res: HashMap<String, &'static K> = self
+            .declared
+            .iter()
+            .map(|(channel, codec)| (channel.clone(), *codec))
+            .collect();
+        for scope in &[None, Some(engine)] {
+            for ((e, channel), route) in &self.exact {
+                if e == scope {
+                    res.insert(channel.clone(), route.codec);
+                }
+            }
+        }
+        res
+    }
+}
+
+// Channel registered on engine along with the codec it was registered with
+struct RegisteredChannel<T, K: ?Sized + 'static> {
+    channel: T,
+    codec: &'static K,
+}
+
+impl<T, K: ?Sized + 'static> RegisteredChannel<T, K> {
+    // Codecs are compared by address; Spurious mismatch only results in
+    // registering the channel again
+    fn has_codec(&self, codec: &'static K) -> bool {
+        std::ptr::eq(self.codec, codec)
+    }
+}
+
+type RegisteredChannels<T, K> = HashMap<EngineHandle, HashMap<String, RegisteredChannel<T, K>>>;
 
 pub struct MessageManager {
     context: Rc<Context>,
 
-    message_channels: HashMap<EngineHandle, HashMap<String, MessageChannel<Value>>>,
-    message_handlers: Rc<RefCell<HashMap<String, Box<MessageCallback>>>>,
+    message_channels: RegisteredChannels<MessageChannel<Value>, dyn MessageCodec<Value>>,
+    message_handlers: Rc<RefCell<Routes<MessageCallback, dyn MessageCodec<Value>>>>,
 
-    method_channels: HashMap<EngineHandle, HashMap<String, MethodChannel<Value>>>,
-    method_handlers: Rc<RefCell<HashMap<String, Box<MethodCallback>>>>,
+    method_channels: RegisteredChannels<MethodChannel<Value>, dyn MethodCodec<Value>>,
+    method_handlers: Rc<RefCell<Routes<MethodCallback, dyn MethodCodec<Value>>>>,
 }
 
 impl MessageManager {
@@ -25,71 +137,224 @@ impl MessageManager {
         Self {
             context,
             message_channels: HashMap::new(),
-            message_handlers: Rc::new(RefCell::new(HashMap::new())),
+            message_handlers: Rc::new(RefCell::new(Routes::new())),
             method_channels: HashMap::new(),
-            method_handlers: Rc::new(RefCell::new(HashMap::new())),
+            method_handlers: Rc::new(RefCell::new(Routes::new())),
         }
     }
 
-    pub fn register_message_handler<F>(&mut self, channel: &str, callback: F)
+    // Registers message handler for given channel on all engines
+    pub fn register_message_handler<F>(
+        &mut self,
+        channel: &str,
+        codec: &'static dyn MessageCodec<Value>,
+        callback: F,
+    ) where
+        F: Fn(Value, MessageReply<Value>, EngineHandle) + 'static,
+    {
+        self.message_handlers.borrow_mut().exact.insert(
+            (None, channel.into()),
+            ExactRoute {
+                handler: Rc::new(move |value, reply, engine, _channel: &str| {
+                    callback(value, reply, engine)
+                }),
+                codec,
+            },
+        );
+        self.reset_message_channel(None, channel);
+    }
+
+    // Registers message handler for given channel on single engine; Takes precedence
+    // over handler registered for all engines
+    pub fn register_message_handler_for_engine<F>(
+        &mut self,
+        engine: EngineHandle,
+        channel: &str,
+        codec: &'static dyn MessageCodec<Value>,
+        callback: F,
+    ) -> Result<()>
     where
         F: Fn(Value, MessageReply<Value>, EngineHandle) + 'static,
     {
-        let context = self.context.clone();
-        if !self
-            .message_handlers
-            .as_ref()
-            .borrow()
-            .contains_key(channel)
-        {
-            // register handlers on engines
-            let manager = context.engine_manager.borrow();
-            let engines = manager.get_all_engines();
-            for engine in engines {
-                self.register_message_channel_for_engine(&manager, engine, channel);
-            }
+        self.check_engine(engine)?;
+        self.message_handlers.borrow_mut().exact.insert(
+            (Some(engine), channel.into()),
+            ExactRoute {
+                handler: Rc::new(move |value, reply, engine, _channel: &str| {
+                    callback(value, reply, engine)
+                }),
+                codec,
+            },
+        );
+        self.reset_message_channel(Some(engine), channel);
+        Ok(())
+    }
+
+    // Registers message handler for all channels starting with prefix (on all engines
+    // if engine is None). Channels served only by prefix handlers must be declared
+    // with add_message_channel, otherwise engine will not deliver the messages.
+    pub fn register_message_prefix_handler<F>(
+        &mut self,
+        prefix: &str,
+        engine: Option<EngineHandle>,
+        callback: F,
+    ) -> Result<()>
+    where
+        F: Fn(Value, MessageReply<Value>, EngineHandle, &str) + 'static,
+    {
+        if let Some(engine) = engine {
+            self.check_engine(engine)?;
         }
+        self.message_handlers
+            .borrow_mut()
+            .prefix
+            .insert((engine, prefix.into()), Rc::new(callback));
+        Ok(())
+    }
 
+    pub fn add_message_channel(&mut self, channel: &str, codec: &'static dyn MessageCodec<Value>) {
         self.message_handlers
-            .as_ref()
             .borrow_mut()
-            .insert(channel.into(), Box::new(callback));
+            .declared
+            .insert(channel.into(), codec);
+        self.reset_message_channel(None, channel);
+    }
+
+    pub fn remove_message_channel(&mut self, channel: &str) {
+        self.message_handlers.borrow_mut().declared.remove(channel);
+        self.update_message_channels(None);
+    }
+
+    // Registers method handler for given channel on all engines
+    pub fn register_method_handler<F>(
+        &mut self,
+        channel: &str,
+        codec: &'static dyn MethodCodec<Value>,
+        callback: F,
+    ) where
+        F: Fn(MethodCall<Value>, MethodCallReply<Value>, EngineHandle) + 'static,
+    {
+        self.method_handlers.borrow_mut().exact.insert(
+            (None, channel.into()),
+            ExactRoute {
+                handler: Rc::new(move |call, reply, engine, _channel: &str| {
+                    callback(call, reply, engine)
+                }),
+                codec,
+            },
+        );
+        self.reset_method_channel(None, channel);
     }
 
-    pub fn register_method_handler<F>(&mut self, channel: &str, callback: F)
+    // Registers method handler for given channel on single engine; Takes precedence
+    // over handler registered for all engines
+    pub fn register_method_handler_for_engine<F>(
+        &mut self,
+        engine: EngineHandle,
+        channel: &str,
+        codec: &'static dyn MethodCodec<Value>,
+        callback: F,
+    ) -> Result<()>
     where
         F: Fn(MethodCall<Value>, MethodCallReply<Value>, EngineHandle) + 'static,
     {
-        let context = self.context.clone();
-        if !self.method_handlers.as_ref().borrow().contains_key(channel) {
-            // register handlers on engines
-            let manager = context.engine_manager.borrow();
-            let engines = manager.get_all_engines();
-            for engine in engines {
-                self.register_method_channel_for_engine(&manager, engine, channel);
-            }
+        self.check_engine(engine)?;
+        self.method_handlers.borrow_mut().exact.insert(
+            (Some(engine), channel.into()),
+            ExactRoute {
+                handler: Rc::new(move |call, reply, engine, _channel: &str| {
+                    callback(call, reply, engine)
+                }),
+                codec,
+            },
+        );
+        self.reset_method_channel(Some(engine), channel);
+        Ok(())
+    }
+
+    // Registers method handler for all channels starting with prefix (on all engines
+    // if engine is None). Channels served only by prefix handlers must be declared
+    // with add_method_channel, otherwise engine will not deliver the calls.
+    pub fn register_method_prefix_handler<F>(
+        &mut self,
+        prefix: &str,
+        engine: Option<EngineHandle>,
+        callback: F,
+    ) -> Result<()>
+    where
+        F: Fn(MethodCall<Value>, MethodCallReply<Value>, EngineHandle, &str) + 'static,
+    {
+        if let Some(engine) = engine {
+            self.check_engine(engine)?;
         }
+        self.method_handlers
+            .borrow_mut()
+            .prefix
+            .insert((engine, prefix.into()), Rc::new(callback));
+        Ok(())
+    }
 
+    pub fn add_method_channel(&mut self, channel: &str, codec: &'static dyn MethodCodec<Value>) {
         self.method_handlers
-            .as_ref()
             .borrow_mut()
-            .insert(channel.into(), Box::new(callback));
+            .declared
+            .insert(channel.into(), codec);
+        self.reset_method_channel(None, channel);
+    }
+
+    pub fn remove_method_channel(&mut self, channel: &str) {
+        self.method_handlers.borrow_mut().declared.remove(channel);
+        self.update_method_channels(None);
     }
 
     pub fn unregister_message_handler(&mut self, channel: &str) {
-        self.message_handlers.as_ref().borrow_mut().remove(channel);
+        self.message_handlers
+            .borrow_mut()
+            .exact
+            .remove(&(None, channel.into()));
+        self.update_message_channels(None);
+    }
 
-        for entry in self.message_channels.values_mut() {
-            entry.remove(channel);
-        }
+    pub fn unregister_message_handler_for_engine(&mut self, engine: EngineHandle, channel: &str) {
+        self.message_handlers
+            .borrow_mut()
+            .exact
+            .remove(&(Some(engine), channel.into()));
+        self.update_message_channels(Some(engine));
+    }
+
+    pub fn unregister_message_prefix_handler(
+        &mut self,
+        prefix: &str,
+        engine: Option<EngineHandle>,
+    ) {
+        self.message_handlers
+            .borrow_mut()
+            .prefix
+            .remove(&(engine, prefix.into()));
     }
 
     pub fn unregister_method_handler(&mut self, channel: &str) {
-        self.method_handlers.as_ref().borrow_mut().remove(channel);
+        self.method_handlers
+            .borrow_mut()
+            .exact
+            .remove(&(None, channel.into()));
+        self.update_method_channels(None);
+    }
 
-        for entry in self.method_channels.values_mut() {
-            entry.remove(channel);
-        }
+    pub fn unregister_method_handler_for_engine(&mut self, engine: EngineHandle, channel: &str) {
+        self.method_handlers
+            .borrow_mut()
+            .exact
+            .remove(&(Some(engine), channel.into()));
+        self.update_method_channels(Some(engine));
+    }
+
+    pub fn unregister_method_prefix_handler(&mut self, prefix: &str, engine: Option<EngineHandle>) {
+        self.method_handlers
+            .borrow_mut()
+            .prefix
+            .remove(&(engine, prefix.into()));
     }
 
     pub fn get_message_sender(
@@ -100,7 +365,7 @@ impl MessageManager {
         self.message_channels
             .get(&engine)
             .and_then(|e| e.get(channel))
-            .and_then(|e| Some(e.sender().clone()))
+            .and_then(|e| Some(e.channel.sender().clone()))
     }
 
     pub fn get_method_invoker(
@@ -111,57 +376,161 @@ impl MessageManager {
         self.method_channels
             .get(&engine)
             .and_then(|e| e.get(channel))
-            .and_then(|e| Some(e.invoker().clone()))
+            .and_then(|e| Some(e.channel.invoker().clone()))
+    }
+
+    pub(super) fn message_handler_infos(&self) -> Vec<HandlerInfo> {
+        self.message_handlers.borrow().handler_infos()
+    }
+
+    pub(super) fn method_handler_infos(&self) -> Vec<HandlerInfo> {
+        self.method_handlers.borrow().handler_infos()
     }
 
     pub(super) fn engine_created(&mut self, engine_manager: &EngineManager, engine: EngineHandle) {
-        let message_keys: Vec<String> = self
-            .message_handlers
-            .as_ref()
-            .borrow()
-            .keys()
-            .map(|s| s.into())
-            .collect();
-        for channel in message_keys {
-            self.register_message_channel_for_engine(engine_manager, engine, &channel);
-        }
+        self.update_message_channels_for_engine(engine_manager, engine);
+        self.update_method_channels_for_engine(engine_manager, engine);
+    }
 
-        let method_keys: Vec<String> = self
-            .method_handlers
-            .as_ref()
+    // Drops handlers registered for the engine and its channels; Called by engine
+    // manager after the engine has been removed
+    pub(super) fn engine_removed(&mut self, engine: EngineHandle) {
+        self.message_handlers.borrow_mut().remove_engine(engine);
+        self.method_handlers.borrow_mut().remove_engine(engine);
+        let message_channels = self.message_channels.remove(&engine);
+        let method_channels = self.method_channels.remove(&engine);
+        // dropping channels borrows engine manager, which is mutably borrowed now
+        self.context
+            .run_loop
             .borrow()
-            .keys()
-            .map(|s| s.into())
-            .collect();
+            .schedule(
+                move || {
+                    drop(message_channels);
+                    drop(method_channels);
+                },
+                Duration::from_secs(0),
+            )
+            .detach();
+    }
 
-        for channel in method_keys {
-            self.register_method_channel_for_engine(engine_manager, engine, &channel);
+    // Handlers for unknown engine would never be removed
+    fn check_engine(&self, engine: EngineHandle) -> Result<()> {
+        match self.context.engine_manager.borrow().get_engine(engine) {
+            Some(_) => Ok(()),
+            None => Err(Error::InvalidEngineHandle(engine)),
         }
     }
 
     fn on_message(
-        handlers: Rc<RefCell<HashMap<String, Box<MessageCallback>>>>,
+        handlers: Rc<RefCell<Routes<MessageCallback, dyn MessageCodec<Value>>>>,
         value: Value,
         channel: &str,
         reply: MessageReply<Value>,
         engine: EngineHandle,
     ) {
-        let handlers = handlers.as_ref().borrow();
-        if let Some(handler) = handlers.get(channel) {
-            handler(value, reply, engine);
+        // don't keep handlers borrowed during the call, the handler might
+        // register or unregister other handlers
+        let handler = handlers.borrow().find(engine, channel);
+        if let Some(handler) = handler {
+            handler(value, reply, engine, channel);
         }
     }
 
     fn on_method(
-        handlers: Rc<RefCell<HashMap<String, Box<MethodCallback>>>>,
+        handlers: Rc<RefCell<Routes<MethodCallback, dyn MethodCodec<Value>>>>,
         call: MethodCall<Value>,
         channel: &str,
         reply: MethodCallReply<Value>,
         engine: EngineHandle,
     ) {
-        let handlers = handlers.as_ref().borrow();
-        if let Some(handler) = handlers.get(channel) {
-            handler(call, reply, engine);
+        let handler = handlers.borrow().find(engine, channel);
+        if let Some(handler) = handler {
+            handler(call, reply, engine, channel);
+        }
+    }
+
+    // Drops existing registration of the channel so that it gets registered again
+    // with current codec
+    fn reset_message_channel(&mut self, engine: Option<EngineHandle>, channel: &str) {
+        for (e, channels) in self.message_channels.iter_mut() {
+            if engine.is_none() || engine == Some(*e) {
+                channels.remove(channel);
+            }
+        }
+        self.update_message_channels(engine);
+    }
+
+    fn reset_method_channel(&mut self, engine: Option<EngineHandle>, channel: &str) {
+        for (e, channels) in self.method_channels.iter_mut() {
+            if engine.is_none() || engine == Some(*e) {
+                channels.remove(channel);
+            }
+        }
+        self.update_method_channels(engine);
+    }
+
+    // Makes sure that channels registered on engine(s) match the routing table
+    fn update_message_channels(&mut self, engine: Option<EngineHandle>) {
+        let context = self.context.clone();
+        let manager = context.engine_manager.borrow();
+        let engines = match engine {
+            Some(engine) => vec![engine],
+            None => manager.get_all_engines(),
+        };
+        for engine in engines {
+            self.update_message_channels_for_engine(&manager, engine);
+        }
+    }
+
+    fn update_method_channels(&mut self, engine: Option<EngineHandle>) {
+        let context = self.context.clone();
+        let manager = context.engine_manager.borrow();
+        let engines = match engine {
+            Some(engine) => vec![engine],
+            None => manager.get_all_engines(),
+        };
+        for engine in engines {
+            self.update_method_channels_for_engine(&manager, engine);
+        }
+    }
+
+    fn update_message_channels_for_engine(
+        &mut self,
+        engine_manager: &EngineManager,
+        engine: EngineHandle,
+    ) {
+        let channels = self.message_handlers.borrow().channels_for_engine(engine);
+        let registered = self.message_channels.entry(engine).or_default();
+        // channel resolved to different route might need different codec
+        registered.retain(|channel, registered| {
+            matches!(channels.get(channel), Some(codec) if registered.has_codec(*codec))
+        });
+        let missing: Vec<_> = channels
+            .into_iter()
+            .filter(|(channel, _)| !registered.contains_key(channel))
+            .collect();
+        for (channel, codec) in missing {
+            self.register_message_channel_for_engine(engine_manager, engine, &channel, codec);
+        }
+    }
+
+    fn update_method_channels_for_engine(
+        &mut self,
+        engine_manager: &EngineManager,
+        engine: EngineHandle,
+    ) {
+        let channels = self.method_handlers.borrow().channels_for_engine(engine);
+        let registered = self.method_channels.entry(engine).or_default();
+        // channel resolved to different route might need different codec
+        registered.retain(|channel, registered| {
+            matches!(channels.get(channel), Some(codec) if registered.has_codec(*codec))
+        });
+        let missing: Vec<_> = channels
+            .into_iter()
+            .filter(|(channel, _)| !registered.contains_key(channel))
+            .collect();
+        for (channel, codec) in missing {
+            self.register_method_channel_for_engine(engine_manager, engine, &channel, codec);
         }
     }
 
@@ -170,6 +539,7 @@ impl MessageManager {
         engine_manager: &EngineManager,
         engine: EngineHandle,
         channel: &str,
+        codec: &'static dyn MessageCodec<Value>,
     ) {
         let channel_str = String::from(channel);
         let handlers = self.message_handlers.clone();
@@ -177,7 +547,7 @@ impl MessageManager {
             self.context.clone(),
             engine,
             channel,
-            &StandardMethodCodec,
+            codec,
             move |value, reply| {
                 let engine = engine.clone();
                 Self::on_message(handlers.clone(), value, &channel_str, reply, engine);
@@ -186,7 +556,13 @@ impl MessageManager {
         );
         let map = self.message_channels.entry(engine);
         let entry = map.or_insert_with(|| HashMap::new());
-        entry.insert(channel.into(), message_channel);
+        entry.insert(
+            channel.into(),
+            RegisteredChannel {
+                channel: message_channel,
+                codec,
+            },
+        );
     }
 
     fn register_method_channel_for_engine(
@@ -194,6 +570,7 @@ impl MessageManager {
         engine_manager: &EngineManager,
         engine: EngineHandle,
         channel: &str,
+        codec: &'static dyn MethodCodec<Value>,
     ) {
         let channel_str = String::from(channel);
         let handlers = self.method_handlers.clone();
@@ -201,7 +578,7 @@ impl MessageManager {
             self.context.clone(),
             engine,
             channel,
-            &StandardMethodCodec,
+            codec,
             move |call, reply| {
                 let engine = engine.clone();
                 Self::on_method(handlers.clone(), call, &channel_str, reply, engine);
@@ -210,6 +587,224 @@ impl MessageManager {
         );
         let map = self.method_channels.entry(engine);
         let entry = map.or_insert_with(|| HashMap::new());
-        entry.insert(channel.into(), method_channel);
+        entry.insert(
+            channel.into(),
+            RegisteredChannel {
+                channel: method_channel,
+                codec,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        codec::{
+            MessageCodec, MethodCall, MethodCallError, MethodCodec, StandardMethodCodec, Value,
+        },
+        shell::{Context, EngineHandle},
+        Error,
+    };
+
+    use super::{ExactRoute, MessageManager, Routes};
+
+    // Encodes same as standard codec but is a distinct codec
+    struct OtherCodec;
+
+    impl MessageCodec<Value> for OtherCodec {
+        fn encode_message(&self, v: &Value) -> Vec<u8> {
+            StandardMethodCodec.encode_message(v)
+        }
+
+        fn decode_message(&self, buf: &[u8]) -> Option<Value> {
+            StandardMethodCodec.decode_message(buf)
+        }
+    }
+
+    impl MethodCodec<Value> for OtherCodec {
+        fn decode_method_call(&self, buf: &[u8]) -> Option<MethodCall<Value>> {
+            StandardMethodCodec.decode_method_call(buf)
+        }
+
+        fn encode_success_envelope(&self, v: &Value) -> Vec<u8> {
+            StandardMethodCodec.encode_success_envelope(v)
+        }
+
+        fn encode_error_envelope(
+            &self,
+            code: &str,
+            message: Option<&str>,
+            details: &Value,
+        ) -> Vec<u8> {
+            StandardMethodCodec.encode_error_envelope(code, message, details)
+        }
+
+        fn encode_method_call(&self, v: &MethodCall<Value>) -> Vec<u8> {
+            StandardMethodCodec.encode_method_call(v)
+        }
+
+        fn decode_envelope(&self, buf: &[u8]) -> Option<Result<Value, MethodCallError<Value>>> {
+            StandardMethodCodec.decode_envelope(buf)
+        }
+    }
+
+    static STANDARD_CODEC: StandardMethodCodec = StandardMethodCodec;
+    static OTHER_CODEC: OtherCodec = OtherCodec;
+
+    #[test]
+    fn test_codec_after_engine_handler_removed() {
+        let context = Context::new(Default::default()).unwrap();
+        let engine = context.engine_manager.borrow_mut().create_engine();
+        let mut manager = context.message_manager.borrow_mut();
+
+        manager.register_message_handler("channel", &STANDARD_CODEC, |_, _, _| {});
+        manager
+            .register_message_handler_for_engine(engine, "channel", &OTHER_CODEC, |_, _, _| {})
+            .unwrap();
+        assert!(manager.message_channels[&engine]["channel"].has_codec(&OTHER_CODEC));
+        manager.unregister_message_handler_for_engine(engine, "channel");
+        assert!(manager.message_channels[&engine]["channel"].has_codec(&STANDARD_CODEC));
+
+        manager.register_method_handler("channel", &STANDARD_CODEC, |_, _, _| {});
+        manager
+            .register_method_handler_for_engine(engine, "channel", &OTHER_CODEC, |_, _, _| {})
+            .unwrap();
+        assert!(manager.method_channels[&engine]["channel"].has_codec(&OTHER_CODEC));
+        manager.unregister_method_handler_for_engine(engine, "channel");
+        assert!(manager.method_channels[&engine]["channel"].has_codec(&STANDARD_CODEC));
+    }
+
+    fn exact(handler: &str, codec: &'static str) -> ExactRoute<str, str> {
+        ExactRoute {
+            handler: handler.into(),
+            codec,
+        }
+    }
+
+    fn find(routes: &Routes<str, str>, engine: EngineHandle, channel: &str) -> Option<String> {
+        routes
+            .find(engine, channel)
+            .map(|handler| handler.to_string())
+    }
+
+    #[test]
+    fn test_find_route() {
+        let e1 = EngineHandle(1);
+        let e2 = EngineHandle(2);
+        let mut routes = Routes::<str, str>::new();
+        routes.exact.insert((None, "a".into()), exact("exact", "a"));
+        routes
+            .exact
+            .insert((Some(e1), "a".into()), exact("exact-e1", "a"));
+        routes
+            .exact
+            .insert((None, "p.exact".into()), exact("exact", "a"));
+        routes.prefix.insert((None, "p".into()), "prefix".into());
+        routes
+            .prefix
+            .insert((None, "p.long".into()), "prefix-long".into());
+        routes
+            .prefix
+            .insert((Some(e1), "p".into()), "prefix-e1".into());
+
+        assert_eq!(find(&routes, e1, "a").as_deref(), Some("exact-e1"));
+        assert_eq!(find(&routes, e2, "a").as_deref(), Some("exact"));
+
+        // exact route wins over any prefix
+        assert_eq!(find(&routes, e1, "p.exact").as_deref(), Some("exact"));
+
+        // prefix for engine wins over longer prefix for all engines
+        assert_eq!(find(&routes, e1, "p.long.x").as_deref(), Some("prefix-e1"));
+        assert_eq!(
+            find(&routes, e2, "p.long.x").as_deref(),
+            Some("prefix-long")
+        );
+        assert_eq!(find(&routes, e2, "p.x").as_deref(), Some("prefix"));
+
+        assert_eq!(find(&routes, e1, "b"), None);
+    }
+
+    #[test]
+    fn test_channels_for_engine() {
+        let e1 = EngineHandle(1);
+        let e2 = EngineHandle(2);
+        let mut routes = Routes::<str, str>::new();
+        routes.declared.insert("declared".into(), "declared");
+        routes.declared.insert("a".into(), "declared");
+        routes
+            .exact
+            .insert((None, "a".into()), exact("exact", "exact"));
+        routes
+            .exact
+            .insert((Some(e1), "a".into()), exact("exact-e1", "exact-e1"));
+        routes
+            .exact
+            .insert((Some(e1), "b".into()), exact("exact-e1", "exact-e1"));
+        routes.prefix.insert((None, "p".into()), "prefix".into());
+
+        let channels = routes.channels_for_engine(e1);
+        assert_eq!(channels.len(), 3);
+        assert_eq!(channels["declared"], "declared");
+        assert_eq!(channels["a"], "exact-e1");
+        assert_eq!(channels["b"], "exact-e1");
+
+        let channels = routes.channels_for_engine(e2);
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels["a"], "exact");
+
+        routes.remove_engine(e1);
+        assert_eq!(find(&routes, e1, "a").as_deref(), Some("exact"));
+        assert_eq!(routes.channels_for_engine(e1).len(), 2);
+    }
+
+    #[test]
+    fn test_engine_removed() {
+        let context = Context::new(Default::default()).unwrap();
+        let engine = context.engine_manager.borrow_mut().create_engine();
+        let handler_count = |manager: &MessageManager| {
+            manager.message_handler_infos().len() + manager.method_handler_infos().len()
+        };
+        let initial_count = handler_count(&context.message_manager.borrow());
+        {
+            let mut manager = context.message_manager.borrow_mut();
+            manager
+                .register_message_handler_for_engine(
+                    engine,
+                    "channel",
+                    &STANDARD_CODEC,
+                    |_, _, _| {},
+                )
+                .unwrap();
+            manager
+                .register_method_prefix_handler("prefix", Some(engine), |_, _, _, _| {})
+                .unwrap();
+            assert!(manager.get_message_sender(engine, "channel").is_some());
+            assert_eq!(handler_count(&manager), initial_count + 2);
+        }
+
+        context
+            .engine_manager
+            .borrow_mut()
+            .remove_engine(engine)
+            .ok();
+        let run_loop = context.run_loop.borrow().clone();
+        run_loop.run();
+
+        let mut manager = context.message_manager.borrow_mut();
+        assert_eq!(handler_count(&manager), initial_count);
+        assert!(manager.get_message_sender(engine, "channel").is_none());
+
+        // registrations for removed engine are rejected
+        let res = manager.register_message_handler_for_engine(
+            engine,
+            "channel",
+            &STANDARD_CODEC,
+            |_, _, _| {},
+        );
+        assert!(matches!(res, Err(Error::InvalidEngineHandle(e)) if e == engine));
+        let res = manager.register_method_prefix_handler("prefix", Some(engine), |_, _, _, _| {});
+        assert!(matches!(res, Err(Error::InvalidEngineHandle(e)) if e == engine));
+        assert_eq!(handler_count(&manager), initial_count);
     }
 }
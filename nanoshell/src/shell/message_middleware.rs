@@ -0,0 +1,212 @@
+use std::rc::Rc;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageDirection {
+    // Message sent from Dart, handled by channel handler on native side
+    Incoming,
+    // Message sent from native side to Dart
+    Outgoing,
+}
+
+pub struct InterceptedMessage {
+    pub channel: String,
+    pub direction: MessageDirection,
+    pub data: Vec<u8>,
+}
+
+// Middleware installed on BinaryMessenger; Can observe, transform, delay or swallow
+// messages and replies passing through the messenger. Default implementation
+// forwards everything unchanged.
+//
+// Key events are sent by Dart as incoming messages on `nanoshell/keyevent` before
+// reaching Flutter; Swallowing such message marks the key event as handled.
+pub trait MessageMiddleware {
+    // Called for every message on matching channel. Message is passed on by calling
+    // `next.forward`, possibly later or with different data. Dropping `next` swallows
+    // the message; For incoming message an empty reply will be sent, for outgoing
+    // message the reply callback is never called.
+    fn on_message(&self, message: InterceptedMessage, next: MessageForwarder) {
+        next.forward(message.data);
+    }
+
+    // Called for reply to a message. For incoming message this is the reply sent
+    // by native handler, for outgoing message the reply received from Dart.
+    // Replies go through middlewares in reverse order.
+    fn on_reply(&self, reply: InterceptedMessage, next: MessageForwarder) {
+        next.forward(reply.data);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MiddlewareHandle(pub(super) usize);
+
+pub(super) type MiddlewareChain = Rc<Vec<Rc<dyn MessageMiddleware>>>;
+
+#[derive(Clone, Copy)]
+enum Stage {
+    Message,
+    Reply,
+}
+
+pub struct MessageForwarder {
+    chain: MiddlewareChain,
+    index: usize,
+    stage: Stage,
+    channel: String,
+    direction: MessageDirection,
+    sink: Box<dyn FnOnce(Vec<u8>)>,
+}
+
+impl MessageForwarder {
+    // Passes data to next middleware in chain, or delivers it if this is the last one
+    pub fn forward(self, data: Vec<u8>) {
+        match self.chain.get(self.index).cloned() {
+            Some(middleware) => {
+                let message = InterceptedMessage {
+                    channel: self.channel.clone(),
+                    direction: self.direction,
+                    data,
+                };
+                let stage = self.stage;
+                let next = MessageForwarder {
+                    index: self.index + 1,
+                    ..self
+                };
+                match stage {
+                    Stage::Message => middleware.on_message(message, next),
+                    Stage::Reply => middleware.on_reply(message, next),
+                }
+            }
+            None => (self.sink)(data),
+        }
+    }
+
+    pub(super) fn dispatch_message<F>(
+        chain: MiddlewareChain,
+        channel: &str,
+        direction: MessageDirection,
+        data: Vec<u8>,
+        sink: F,
+    ) where
+        F: FnOnce(Vec<u8>) + 'static,
+    {
+        Self::dispatch(chain, Stage::Message, channel, direction, data, sink)
+    }
+
+    // Expects chain in reverse order
+    pub(super) fn dispatch_reply<F>(
+        chain: MiddlewareChain,
+        channel: &str,
+        direction: MessageDirection,
+        data: Vec<u8>,
+        sink: F,
+    ) where
+        F: FnOnce(Vec<u8>) + 'static,
+    {
+        Self::dispatch(chain, Stage::Reply, channel, direction, data, sink)
+    }
+
+    fn dispatch<F>(
+        chain: MiddlewareChain,
+        stage: Stage,
+        channel: &str,
+        direction: MessageDirection,
+        data: Vec<u8>,
+        sink: F,
+    ) where
+        F: FnOnce(Vec<u8>) + 'static,
+    {
+        MessageForwarder {
+            chain,
+            index: 0,
+            stage,
+            channel: channel.into(),
+            direction,
+            sink: Box::new(sink),
+        }
+        .forward(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+
+    struct PassThrough;
+
+    impl MessageMiddleware for PassThrough {}
+
+    struct Append(u8);
+
+    impl MessageMiddleware for Append {
+        fn on_message(&self, mut message: InterceptedMessage, next: MessageForwarder) {
+            message.data.push(self.0);
+            next.forward(message.data);
+        }
+
+        fn on_reply(&self, mut reply: InterceptedMessage, next: MessageForwarder) {
+            reply.data.push(self.0);
+            next.forward(reply.data);
+        }
+    }
+
+    #[derive(Default)]
+    struct Swallow {
+        seen: RefCell<Option<(String, MessageDirection, Vec<u8>)>>,
+    }
+
+    impl MessageMiddleware for Swallow {
+        fn on_message(&self, message: InterceptedMessage, _next: MessageForwarder) {
+            self.seen
+                .replace(Some((message.channel, message.direction, message.data)));
+        }
+    }
+
+    fn dispatch(chain: Vec<Rc<dyn MessageMiddleware>>, reply: bool) -> Option<Vec<u8>> {
+        let result = Rc::new(RefCell::new(None));
+        let result_copy = result.clone();
+        let sink = move |data| {
+            result_copy.replace(Some(data));
+        };
+        let chain = Rc::new(chain);
+        let direction = MessageDirection::Outgoing;
+        if reply {
+            MessageForwarder::dispatch_reply(chain, "channel", direction, vec![0], sink);
+        } else {
+            MessageForwarder::dispatch_message(chain, "channel", direction, vec![0], sink);
+        }
+        result.take()
+    }
+
+    #[test]
+    fn test_pass_through() {
+        assert_eq!(dispatch(vec![], false), Some(vec![0]));
+        assert_eq!(dispatch(vec![Rc::new(PassThrough)], false), Some(vec![0]));
+        assert_eq!(dispatch(vec![Rc::new(PassThrough)], true), Some(vec![0]));
+    }
+
+    #[test]
+    fn test_rewrite() {
+        let chain: Vec<Rc<dyn MessageMiddleware>> =
+            vec![Rc::new(Append(1)), Rc::new(PassThrough), Rc::new(Append(2))];
+        assert_eq!(dispatch(chain.clone(), false), Some(vec![0, 1, 2]));
+        assert_eq!(dispatch(chain, true), Some(vec![0, 1, 2]));
+    }
+
+    #[test]
+    fn test_swallow() {
+        let swallow = Rc::new(Swallow::default());
+        let chain: Vec<Rc<dyn MessageMiddleware>> =
+            vec![Rc::new(Append(1)), swallow.clone(), Rc::new(Append(2))];
+        assert_eq!(dispatch(chain, false), None);
+        assert_eq!(
+            swallow.seen.take(),
+            Some(("channel".into(), MessageDirection::Outgoing, vec![0, 1]))
+        );
+
+        // default on_reply passes the reply on
+        assert_eq!(dispatch(vec![swallow], true), Some(vec![0]));
+    }
+}
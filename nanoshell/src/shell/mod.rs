@@ -1,27 +1,48 @@
+#[cfg(all(feature = "automation", unix))]
+mod automation;
 mod binary_messenger;
+mod call_options;
+mod channel_metrics;
 mod constants;
 mod context;
+mod diagnostics;
+mod display_layout;
+mod display_manager;
 mod engine;
 mod engine_manager;
 mod geometry;
 mod menu_manager;
 mod message_manager;
+mod message_middleware;
+mod reply_aggregator;
 mod run_loop;
 mod window;
 mod window_manager;
 mod window_method_channel;
+mod window_placement;
+mod window_proxy;
+mod window_state_store;
 
 pub use binary_messenger::*;
+pub use call_options::*;
+pub use channel_metrics::*;
 pub use context::*;
+pub use diagnostics::*;
+pub use display_layout::*;
+pub use display_manager::*;
 pub use engine::*;
 pub use engine_manager::*;
 pub use geometry::*;
 pub use menu_manager::*;
 pub use message_manager::*;
+pub use message_middleware::*;
 pub use run_loop::*;
 pub use window::*;
 pub use window_manager::*;
 pub use window_method_channel::*;
+pub use window_placement::*;
+pub use window_proxy::*;
+pub use window_state_store::*;
 
 pub mod platform;
 pub mod structs;
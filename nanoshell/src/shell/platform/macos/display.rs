@@ -0,0 +1,87 @@
+use std::rc::Rc;
+
+use block::ConcreteBlock;
+use cocoa::{
+    appkit::NSScreen,
+    base::{id, nil},
+    foundation::{NSArray, NSRect, NSUInteger},
+};
+use objc::rc::{autoreleasepool, StrongPtr};
+
+use crate::shell::{structs::Display, Context, Rect};
+
+use super::utils::to_nsstring;
+
+pub struct PlatformDisplayManager {
+    observer: StrongPtr,
+}
+
+impl PlatformDisplayManager {
+    pub fn new(context: Rc<Context>) -> Self {
+        let context = Rc::downgrade(&context);
+        let callback = move |_notification: id| {
+            if let Some(context) = context.upgrade() {
+                context.display_manager.borrow().displays_changed();
+            }
+        };
+        let observer = unsafe {
+            let block = ConcreteBlock::new(callback).copy();
+            let center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+            let observer: id = msg_send![center,
+                addObserverForName: *to_nsstring("NSApplicationDidChangeScreenParametersNotification")
+                object: nil
+                queue: nil
+                usingBlock: &*block];
+            StrongPtr::retain(observer)
+        };
+        Self { observer }
+    }
+
+    pub fn get_displays(&self) -> Vec<Display> {
+        get_displays()
+    }
+}
+
+impl Drop for PlatformDisplayManager {
+    fn drop(&mut self) {
+        unsafe {
+            let center: id = msg_send![class!(NSNotificationCenter), defaultCenter];
+            let () = msg_send![center, removeObserver: *self.observer];
+        }
+    }
+}
+
+fn get_displays() -> Vec<Display> {
+    autoreleasepool(|| unsafe {
+        let screens = NSScreen::screens(nil);
+        let count = NSArray::count(screens);
+        if count == 0 {
+            return Vec::new();
+        }
+        // Cocoa coordinates have bottom left origin at the primary screen
+        let primary_height = NSScreen::frame(screens.objectAtIndex(0)).size.height;
+        let flip = |rect: NSRect| {
+            Rect::xywh(
+                rect.origin.x,
+                primary_height - (rect.origin.y + rect.size.height),
+                rect.size.width,
+                rect.size.height,
+            )
+        };
+        (0..count)
+            .map(|i| {
+                let screen = screens.objectAtIndex(i);
+                let description: id = msg_send![screen, deviceDescription];
+                let number: id =
+                    msg_send![description, objectForKey: *to_nsstring("NSScreenNumber")];
+                let number: NSUInteger = msg_send![number, unsignedIntegerValue];
+                Display {
+                    id: number as i64,
+                    frame: flip(NSScreen::frame(screen)),
+                    work_area: flip(NSScreen::visibleFrame(screen)),
+                    scale: NSScreen::backingScaleFactor(screen),
+                }
+            })
+            .collect()
+    })
+}
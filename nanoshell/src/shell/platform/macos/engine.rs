@@ -1,10 +1,6 @@
-use std::ffi::c_void;
-
 use cocoa::base::{id, nil, BOOL, NO};
 use objc::rc::{autoreleasepool, StrongPtr};
 
-use crate::shell::platform::key_interceptor::override_key_event;
-
 use super::{
     binary_messenger::PlatformBinaryMessenger,
     error::{PlatformError, PlatformResult},
@@ -22,8 +18,6 @@ impl PlatformEngine {
             let view_controller: id = msg_send![class, alloc];
             let view_controller = StrongPtr::new(msg_send![view_controller, initWithProject: nil]);
             let engine: id = msg_send![*view_controller, engine];
-            let embedder_api: *mut c_void = msg_send![engine, embedderAPI];
-            override_key_event(embedder_api);
             Self {
                 handle: StrongPtr::retain(engine),
                 view_controller,
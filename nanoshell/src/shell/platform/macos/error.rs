@@ -1,5 +1,9 @@
 use std::fmt::Display;
 
+use velcro::hash_map;
+
+use crate::{codec::Value, error_code};
+
 #[derive(Debug, Clone)]
 pub enum PlatformError {
     UnknownError,
@@ -11,6 +15,27 @@ pub enum PlatformError {
 
 pub type PlatformResult<T> = Result<T, PlatformError>;
 
+impl PlatformError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            PlatformError::UnknownError => error_code::PLATFORM_UNKNOWN_ERROR,
+            PlatformError::LaunchEngineFailure => error_code::PLATFORM_LAUNCH_ENGINE_FAILURE,
+            PlatformError::SendMessageFailure { .. } => error_code::PLATFORM_SEND_MESSAGE_FAILURE,
+            PlatformError::NotAvailable => error_code::PLATFORM_NOT_AVAILABLE,
+            PlatformError::NoEventFound => error_code::PLATFORM_NO_EVENT_FOUND,
+        }
+    }
+
+    pub fn details(&self) -> Value {
+        match self {
+            PlatformError::SendMessageFailure { channel } => Value::Map(hash_map! {
+                "channel".into() : channel.as_str().into(),
+            }),
+            _ => Value::Null,
+        }
+    }
+}
+
 impl Display for PlatformError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
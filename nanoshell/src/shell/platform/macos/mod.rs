@@ -1,4 +1,5 @@
 pub mod binary_messenger;
+pub mod display;
 mod drag_context;
 pub mod drag_data;
 pub mod engine;
@@ -6,7 +6,7 @@ use std::{
     time::Duration,
 };
 
-use cocoa::appkit::{NSScreen, NSWindowTabbingMode};
+use cocoa::appkit::{NSApplication, NSScreen, NSWindowTabbingMode};
 use cocoa::{
     appkit::{
         NSEvent, NSEventType, NSView, NSWindow, NSWindowCollectionBehavior, NSWindowStyleMask,
@@ -30,9 +30,11 @@ use NSEventType::{NSLeftMouseDown, NSLeftMouseUp, NSMouseMoved, NSRightMouseDown
 use crate::{
     codec::Value,
     shell::{
+        constants::method,
         structs::{
             DragEffect, DragRequest, PopupMenuRequest, PopupMenuResponse, WindowFrame,
-            WindowGeometry, WindowGeometryFlags, WindowGeometryRequest, WindowStyle,
+            WindowGeometry, WindowGeometryFlags, WindowGeometryRequest, WindowLevel, WindowState,
+            WindowStyle,
         },
         Context, PlatformWindowDelegate, Point, Size,
     },
@@ -59,6 +61,7 @@ pub struct PlatformWindow {
     modal_close_callback: RefCell<Option<Box<dyn FnOnce(PlatformResult<Value>) -> ()>>>,
     ready_to_show: Cell<bool>,
     show_when_ready: Cell<bool>,
+    last_state: Cell<WindowState>,
     drag_context: LateRefCell<DragContext>,
     last_event: RefCell<HashMap<u64, StrongPtr>>,
 }
@@ -108,6 +111,7 @@ impl PlatformWindow {
                 modal_close_callback: RefCell::new(None),
                 ready_to_show: Cell::new(false),
                 show_when_ready: Cell::new(false),
+                last_state: Cell::new(WindowState::Normal),
                 last_event: RefCell::new(HashMap::new()),
                 drag_context: LateRefCell::new(),
             }
@@ -342,6 +346,13 @@ impl PlatformWindow {
         }
     }
 
+    pub fn set_title(&self, title: &str) -> PlatformResult<()> {
+        unsafe {
+            NSWindow::setTitle_(*self.platform_window, *to_nsstring(title));
+        }
+        Ok(())
+    }
+
     pub fn set_style(&self, style: WindowStyle) -> PlatformResult<()> {
         unsafe {
             let mut mask: NSWindowStyleMask = NSWindowStyleMask::NSBorderlessWindowMask;
@@ -382,9 +393,127 @@ impl PlatformWindow {
             } else {
                 collection_behavior &= !no_fullscreen;
             }
+            let ignores_cycle: NSWindowCollectionBehavior =
+                std::mem::transmute((1 << 6) as NSUInteger);
+            if style.skip_taskbar {
+                collection_behavior |= ignores_cycle;
+            } else {
+                collection_behavior &= !ignores_cycle;
+            }
             NSWindow::setCollectionBehavior_(*self.platform_window, collection_behavior);
+            let excluded = if style.skip_taskbar { YES } else { NO };
+            let () = msg_send![*self.platform_window, setExcludedFromWindowsMenu: excluded];
 
             NSWindow::setStyleMask_(*self.platform_window, mask);
+
+            // NSNormalWindowLevel, NSFloatingWindowLevel, NSModalPanelWindowLevel
+            let level: NSInteger = match style.level {
+                WindowLevel::Normal => 0,
+                WindowLevel::Floating => 3,
+                WindowLevel::ModalPanel => 8,
+            };
+            let () = msg_send![*self.platform_window, setLevel: level];
+
+            NSWindow::setAlphaValue_(*self.platform_window, style.opacity);
+
+            let transparent = style.transparent && style.frame == WindowFrame::NoFrame;
+            let color: id = if transparent {
+                msg_send![class!(NSColor), clearColor]
+            } else {
+                msg_send![class!(NSColor), windowBackgroundColor]
+            };
+            NSWindow::setOpaque_(*self.platform_window, if transparent { NO } else { YES });
+            NSWindow::setBackgroundColor_(*self.platform_window, color);
+        }
+        Ok(())
+    }
+
+    fn current_state(&self) -> WindowState {
+        unsafe {
+            let window = *self.platform_window;
+            let miniaturized: BOOL = msg_send![window, isMiniaturized];
+            let zoomed: BOOL = msg_send![window, isZoomed];
+            if miniaturized == YES {
+                WindowState::Minimized
+            } else if NSWindow::styleMask(window)
+                .contains(NSWindowStyleMask::NSFullScreenWindowMask)
+            {
+                WindowState::FullScreen
+            } else if zoomed == YES {
+                WindowState::Maximized
+            } else {
+                WindowState::Normal
+            }
+        }
+    }
+
+    fn notify_geometry_changed(&self) {
+        if let (Some(delegate), Some(geometry)) =
+            (self.delegate.upgrade(), self.get_geometry().ok_log())
+        {
+            delegate.geometry_changed(geometry);
+        }
+    }
+
+    fn update_state(&self) {
+        let state = self.current_state();
+        if self.last_state.replace(state) != state {
+            if let Some(delegate) = self.delegate.upgrade() {
+                delegate.state_changed(state);
+            }
+        }
+    }
+
+    pub fn set_state(&self, state: WindowState) -> PlatformResult<()> {
+        let current = self.current_state();
+        if current == state {
+            return Ok(());
+        }
+        unsafe {
+            let window = *self.platform_window;
+            // Leave current state first; Note that full screen transition is animated,
+            // state-changed event is delivered once it finishes
+            match current {
+                WindowState::Minimized => {
+                    let () = msg_send![window, deminiaturize: nil];
+                }
+                WindowState::FullScreen => {
+                    let () = msg_send![window, toggleFullScreen: nil];
+                }
+                WindowState::Maximized if state == WindowState::Normal => {
+                    let () = msg_send![window, zoom: nil];
+                }
+                _ => {}
+            }
+            match state {
+                WindowState::Normal => {}
+                WindowState::Minimized => {
+                    let () = msg_send![window, miniaturize: nil];
+                }
+                WindowState::Maximized => {
+                    let zoomed: BOOL = msg_send![window, isZoomed];
+                    if zoomed == NO {
+                        let () = msg_send![window, zoom: nil];
+                    }
+                }
+                WindowState::FullScreen => {
+                    let () = msg_send![window, toggleFullScreen: nil];
+                }
+            }
+        }
+        self.update_state();
+        Ok(())
+    }
+
+    pub fn get_state(&self) -> PlatformResult<WindowState> {
+        Ok(self.current_state())
+    }
+
+    pub fn activate(&self) -> PlatformResult<()> {
+        unsafe {
+            let app = NSApplication::sharedApplication(nil);
+            NSApplication::activateIgnoringOtherApps_(app, YES);
+            self.platform_window.makeKeyAndOrderFront_(nil);
         }
         Ok(())
     }
@@ -455,6 +584,31 @@ impl PlatformWindow {
         Ok(())
     }
 
+    // Window methods implemented by this backend; Reported to Dart during init
+    pub fn supported_methods() -> &'static [&'static str] {
+        &[
+            method::window::SHOW,
+            method::window::SHOW_MODAL,
+            method::window::READY_TO_SHOW,
+            method::window::HIDE,
+            method::window::CLOSE,
+            method::window::CLOSE_WITH_RESULT,
+            method::window::SET_GEOMETRY,
+            method::window::GET_GEOMETRY,
+            method::window::SUPPORTED_GEOMETRY,
+            method::window::SET_STYLE,
+            method::window::SET_TITLE,
+            method::window::SET_STATE,
+            method::window::GET_STATE,
+            method::window::ACTIVATE,
+            method::window::PERFORM_WINDOW_DRAG,
+            method::window::SHOW_POPUP_MENU,
+            method::window::HIDE_POPUP_MENU,
+            method::window::SET_WINDOW_MENU,
+            method::drag_source::BEGIN_DRAG_SESSION,
+        ]
+    }
+
     pub fn show(&self) -> PlatformResult<()> {
         if self.ready_to_show.get() {
             Self::show_when_ready(self.weak_self.clone_value());
@@ -749,6 +903,31 @@ lazy_static! {
             window_did_move as extern "C" fn(&Object, Sel, id),
         );
 
+        decl.add_method(
+            sel!(windowDidResize:),
+            window_did_resize as extern "C" fn(&Object, Sel, id),
+        );
+
+        decl.add_method(
+            sel!(windowDidMiniaturize:),
+            window_state_changed as extern "C" fn(&Object, Sel, id),
+        );
+
+        decl.add_method(
+            sel!(windowDidDeminiaturize:),
+            window_state_changed as extern "C" fn(&Object, Sel, id),
+        );
+
+        decl.add_method(
+            sel!(windowDidEnterFullScreen:),
+            window_state_changed as extern "C" fn(&Object, Sel, id),
+        );
+
+        decl.add_method(
+            sel!(windowDidExitFullScreen:),
+            window_state_changed as extern "C" fn(&Object, Sel, id),
+        );
+
         decl.add_method(
             sel!(windowShouldClose:),
             window_should_close as extern "C" fn(&Object, Sel, id) -> BOOL,
@@ -821,7 +1000,19 @@ where
 }
 
 extern "C" fn window_did_move(this: &Object, _: Sel, _: id) {
-    with_state_delegate(this, |_state, _delegate| {});
+    with_state(this, |state| state.notify_geometry_changed());
+}
+
+// Zooming has no dedicated notification, so state is also updated on resize
+extern "C" fn window_did_resize(this: &Object, _: Sel, _: id) {
+    with_state(this, |state| {
+        state.update_state();
+        state.notify_geometry_changed();
+    });
+}
+
+extern "C" fn window_state_changed(this: &Object, _: Sel, _: id) {
+    with_state(this, |state| state.update_state());
 }
 
 extern "C" fn window_should_close(this: &Object, _: Sel, _: id) -> BOOL {
@@ -851,24 +1042,26 @@ extern "C" fn window_will_close(this: &Object, _: Sel, _: id) -> () {
 }
 
 extern "C" fn window_did_become_key(this: &Object, _: Sel, _: id) -> () {
-    with_state_delegate(this, |state, _delegate| {
+    with_state_delegate(this, |state, delegate| {
         state
             .context
             .menu_manager
             .borrow()
             .get_platform_menu_manager()
             .window_did_become_active(state.platform_window.clone());
+        delegate.activation_changed(true);
     });
 }
 
 extern "C" fn window_did_resign_key(this: &Object, _: Sel, _: id) -> () {
-    with_state_delegate(this, |state, _delegate| {
+    with_state_delegate(this, |state, delegate| {
         state
             .context
             .menu_manager
             .borrow()
             .get_platform_menu_manager()
             .window_did_resign_active(state.platform_window.clone());
+        delegate.activation_changed(false);
     });
 }
 
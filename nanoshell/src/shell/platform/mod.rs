@@ -9,6 +9,4 @@ mod platform_impl;
 
 #[cfg(target_os = "windows")]
 #[path = "win32/mod.rs"]
-mod platform_impl;
-
-mod key_interceptor;
\ No newline at end of file
+mod platform_impl;
\ No newline at end of file
@@ -1,27 +1,74 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
 use crate::shell::BinaryMessengerReply;
 
 use super::error::{PlatformError, PlatformResult};
 
-pub struct PlatformBinaryMessenger {}
+type ChannelHandler = Rc<dyn Fn(&[u8], BinaryMessengerReply)>;
+
+// There is no engine; Engine side of channels is emulated with handlers set
+// through set_engine_handler and messages delivered with deliver_message.
+#[derive(Default)]
+pub struct PlatformBinaryMessenger {
+    handlers: RefCell<HashMap<String, ChannelHandler>>,
+    engine_handlers: RefCell<HashMap<String, ChannelHandler>>,
+}
 
-#[allow(unused_variables)]
 impl PlatformBinaryMessenger {
     pub fn register_channel_handler<F>(&self, channel: &str, callback: F)
     where
-        F: Fn(&[u8], BinaryMessengerReply) -> () + 'static,
+        F: Fn(&[u8], BinaryMessengerReply) + 'static,
     {
+        self.handlers
+            .borrow_mut()
+            .insert(channel.into(), Rc::new(callback));
     }
 
-    pub fn unregister_channel_handler(&self, channel: &str) {}
+    pub fn unregister_channel_handler(&self, channel: &str) {
+        self.handlers.borrow_mut().remove(channel);
+    }
 
     pub fn send_message<F>(&self, channel: &str, message: &[u8], reply: F) -> PlatformResult<()>
     where
-        F: FnOnce(&[u8]) -> () + 'static,
+        F: FnOnce(&[u8]) + 'static,
     {
-        Err(PlatformError::NotImplemented)
+        let handler = self.engine_handlers.borrow().get(channel).cloned();
+        match handler {
+            Some(handler) => {
+                handler(message, BinaryMessengerReply::new(reply));
+                Ok(())
+            }
+            None => Err(PlatformError::NotImplemented),
+        }
     }
 
     pub fn post_message(&self, channel: &str, message: &[u8]) -> PlatformResult<()> {
-        Err(PlatformError::NotImplemented)
+        self.send_message(channel, message, |_| {})
+    }
+
+    // Handles messages sent to engine on given channel
+    pub fn set_engine_handler<F>(&self, channel: &str, handler: F)
+    where
+        F: Fn(&[u8], BinaryMessengerReply) + 'static,
+    {
+        self.engine_handlers
+            .borrow_mut()
+            .insert(channel.into(), Rc::new(handler));
+    }
+
+    // Delivers message from engine to handler registered for the channel; Returns
+    // false if there is no handler
+    pub fn deliver_message<F>(&self, channel: &str, message: &[u8], reply: F) -> bool
+    where
+        F: FnOnce(&[u8]) + 'static,
+    {
+        let handler = self.handlers.borrow().get(channel).cloned();
+        match handler {
+            Some(handler) => {
+                handler(message, BinaryMessengerReply::new(reply));
+                true
+            }
+            None => false,
+        }
     }
 }
@@ -0,0 +1,37 @@
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use crate::shell::{structs::Display, Context, Rect};
+
+pub struct PlatformDisplayManager {
+    context: Weak<Context>,
+    displays: RefCell<Vec<Display>>,
+}
+
+impl PlatformDisplayManager {
+    pub fn new(context: Rc<Context>) -> Self {
+        Self {
+            context: Rc::downgrade(&context),
+            displays: RefCell::new(vec![Display {
+                id: 1,
+                frame: Rect::xywh(0.0, 0.0, 1920.0, 1080.0),
+                work_area: Rect::xywh(0.0, 0.0, 1920.0, 1080.0),
+                scale: 1.0,
+            }]),
+        }
+    }
+
+    pub fn get_displays(&self) -> Vec<Display> {
+        self.displays.borrow().clone()
+    }
+
+    // Replaces emulated display configuration (single 1920x1080 display by default)
+    pub fn set_displays(&self, displays: Vec<Display>) {
+        self.displays.replace(displays);
+        if let Some(context) = self.context.upgrade() {
+            context.display_manager.borrow().displays_changed();
+        }
+    }
+}
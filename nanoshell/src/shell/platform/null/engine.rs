@@ -8,7 +8,7 @@ impl PlatformEngine {
     }
 
     pub fn new_binary_messenger(&self) -> PlatformBinaryMessenger {
-        PlatformBinaryMessenger {}
+        Default::default()
     }
 
     pub fn launch(&mut self) -> PlatformResult<()> {
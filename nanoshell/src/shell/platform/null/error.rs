@@ -1,5 +1,7 @@
 use std::fmt::Display;
 
+use crate::{codec::Value, error_code};
+
 #[derive(Debug, Clone)]
 pub enum PlatformError {
     NotImplemented,
@@ -8,6 +10,19 @@ pub enum PlatformError {
 
 pub type PlatformResult<T> = Result<T, PlatformError>;
 
+impl PlatformError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            PlatformError::NotImplemented => error_code::PLATFORM_NOT_IMPLEMENTED,
+            PlatformError::UnknownError => error_code::PLATFORM_UNKNOWN_ERROR,
+        }
+    }
+
+    pub fn details(&self) -> Value {
+        Value::Null
+    }
+}
+
 impl Display for PlatformError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{:?}", self)
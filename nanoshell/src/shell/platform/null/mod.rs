@@ -1,4 +1,5 @@
 pub mod binary_messenger;
+pub mod display;
 pub mod drag_data;
 pub mod engine;
 pub mod error;
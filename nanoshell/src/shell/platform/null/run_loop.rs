@@ -1,42 +1,152 @@
-use std::time::Duration;
+use std::{
+    cell::{Cell, RefCell},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 pub type HandleType = usize;
 pub const INVALID_HANDLE: HandleType = 0;
 
-pub struct PlatformRunLoop {}
+struct Scheduled {
+    handle: HandleType,
+    due: Duration,
+    callback: Box<dyn FnOnce()>,
+}
+
+type SentCallbacks = Arc<Mutex<Vec<Box<dyn FnOnce() + Send>>>>;
+
+// Runs scheduled callbacks in order of their due time without actually waiting;
+// Time only advances while running callbacks.
+pub struct PlatformRunLoop {
+    next_handle: Cell<HandleType>,
+    time: Cell<Duration>,
+    scheduled: RefCell<Vec<Scheduled>>,
+    // Callbacks sent from other threads; Picked up by run loop on next turn
+    sent: SentCallbacks,
+    stopped: Cell<bool>,
+}
 
-#[allow(unused_variables)]
 impl PlatformRunLoop {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            next_handle: Cell::new(INVALID_HANDLE + 1),
+            time: Cell::new(Duration::ZERO),
+            scheduled: RefCell::new(Vec::new()),
+            sent: Arc::new(Mutex::new(Vec::new())),
+            stopped: Cell::new(false),
+        }
     }
 
-    pub fn unschedule(&self, handle: HandleType) {}
+    pub fn unschedule(&self, handle: HandleType) {
+        self.scheduled.borrow_mut().retain(|s| s.handle != handle);
+    }
 
     #[must_use]
     pub fn schedule<F>(&self, callback: F, in_time: Duration) -> HandleType
     where
         F: FnOnce() -> () + 'static,
     {
-        INVALID_HANDLE
+        self.push(Box::new(callback), in_time)
     }
 
-    pub fn run(&self) {}
+    fn push(&self, callback: Box<dyn FnOnce()>, in_time: Duration) -> HandleType {
+        let handle = self.next_handle.get();
+        self.next_handle.set(handle + 1);
+        self.scheduled.borrow_mut().push(Scheduled {
+            handle,
+            due: self.time.get() + in_time,
+            callback,
+        });
+        handle
+    }
+
+    // Returns once there are no more scheduled callbacks or the loop was stopped
+    pub fn run(&self) {
+        self.stopped.set(false);
+        while !self.stopped.get() {
+            let sent: Vec<_> = self.sent.lock().unwrap().drain(..).collect();
+            for callback in sent {
+                self.push(callback, Duration::ZERO);
+            }
+            let next = {
+                let mut scheduled = self.scheduled.borrow_mut();
+                let index = scheduled
+                    .iter()
+                    .enumerate()
+                    .min_by_key(|(_, s)| (s.due, s.handle))
+                    .map(|(index, _)| index);
+                index.map(|index| scheduled.remove(index))
+            };
+            match next {
+                Some(next) => {
+                    self.time.set(self.time.get().max(next.due));
+                    (next.callback)();
+                }
+                None => break,
+            }
+        }
+    }
 
-    pub fn stop(&self) {}
+    pub fn stop(&self) {
+        self.stopped.set(true);
+    }
 
     pub fn new_sender(&self) -> PlatformRunLoopSender {
-        PlatformRunLoopSender {}
+        PlatformRunLoopSender {
+            sent: self.sent.clone(),
+        }
     }
 }
 
-pub struct PlatformRunLoopSender {}
+pub struct PlatformRunLoopSender {
+    sent: SentCallbacks,
+}
 
-#[allow(unused_variables)]
 impl PlatformRunLoopSender {
     pub fn send<F>(&self, callback: F)
     where
         F: FnOnce() -> () + 'static + Send,
     {
+        self.sent.lock().unwrap().push(Box::new(callback));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, time::Duration};
+
+    use super::PlatformRunLoop;
+
+    #[test]
+    fn test_run() {
+        let run_loop = Rc::new(PlatformRunLoop::new());
+        let order = Rc::new(RefCell::new(Vec::new()));
+        let record = |name: &'static str| {
+            let order = order.clone();
+            move || order.borrow_mut().push(name)
+        };
+        let _ = run_loop.schedule(record("2s"), Duration::from_secs(2));
+        let _ = run_loop.schedule(record("1s"), Duration::from_secs(1));
+        let cancelled = run_loop.schedule(record("cancelled"), Duration::from_secs(1));
+        run_loop.unschedule(cancelled);
+        let run_loop_copy = run_loop.clone();
+        let nested = record("nested");
+        let _ = run_loop.schedule(
+            move || {
+                // scheduled relative to current time (1.5s)
+                let _ = run_loop_copy.schedule(nested, Duration::from_secs(1));
+            },
+            Duration::from_millis(1500),
+        );
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let run_loop_sender = run_loop.new_sender();
+        std::thread::spawn(move || run_loop_sender.send(move || sender.send("sent").unwrap()))
+            .join()
+            .unwrap();
+
+        run_loop.run();
+        assert_eq!(*order.borrow(), vec!["1s", "2s", "nested"]);
+        assert_eq!(receiver.try_recv(), Ok("sent"));
     }
 }
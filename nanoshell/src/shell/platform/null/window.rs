@@ -1,14 +1,19 @@
-use std::rc::{Rc, Weak};
+use std::{
+    cell::{Cell, RefCell},
+    rc::{Rc, Weak},
+};
 
 use crate::{
     codec::Value,
     shell::{
+        constants::method,
         structs::{
             DragEffect, DragRequest, PopupMenuRequest, PopupMenuResponse, WindowGeometry,
-            WindowGeometryFlags, WindowGeometryRequest, WindowStyle,
+            WindowGeometryFlags, WindowGeometryRequest, WindowState, WindowStyle,
         },
-        Context, PlatformWindowDelegate,
+        Context, PlatformWindowDelegate, Point,
     },
+    util::LateRefCell,
 };
 
 use super::{
@@ -19,7 +24,23 @@ use super::{
 
 pub type PlatformWindowType = isize;
 
-pub struct PlatformWindow {}
+thread_local! {
+    // Emulates key window; Only one window can be active at a time
+    static ACTIVE_WINDOW: RefCell<Weak<PlatformWindow>> = const { RefCell::new(Weak::new()) };
+}
+
+// Headless window; Keeps requested state without displaying anything
+pub struct PlatformWindow {
+    delegate: Weak<dyn PlatformWindowDelegate>,
+    weak_self: LateRefCell<Weak<PlatformWindow>>,
+    title: RefCell<String>,
+    style: RefCell<WindowStyle>,
+    geometry: RefCell<WindowGeometry>,
+    state: Cell<WindowState>,
+    ready_to_show: Cell<bool>,
+    show_when_ready: Cell<bool>,
+    visible: Cell<bool>,
+}
 
 #[allow(unused_variables)]
 impl PlatformWindow {
@@ -28,21 +49,83 @@ impl PlatformWindow {
         delegate: Weak<dyn PlatformWindowDelegate>,
         parent: Option<Rc<PlatformWindow>>,
     ) -> Self {
-        Self {}
+        Self {
+            delegate,
+            weak_self: LateRefCell::new(),
+            title: RefCell::new(String::new()),
+            style: RefCell::new(Default::default()),
+            // like real windows, always have a position
+            geometry: RefCell::new(WindowGeometry {
+                frame_origin: Some(Point::xy(0.0, 0.0)),
+                ..Default::default()
+            }),
+            state: Cell::new(WindowState::Normal),
+            ready_to_show: Cell::new(false),
+            show_when_ready: Cell::new(false),
+            visible: Cell::new(false),
+        }
     }
 
-    pub fn assign_weak_self(&self, weak: Weak<PlatformWindow>, engine: &PlatformEngine) {}
+    pub fn assign_weak_self(&self, weak: Weak<PlatformWindow>, engine: &PlatformEngine) {
+        self.weak_self.set(weak);
+    }
 
     pub fn get_platform_window(&self) -> PlatformWindowType {
         Default::default()
     }
 
+    // Window methods implemented by this backend; Reported to Dart during init
+    pub fn supported_methods() -> &'static [&'static str] {
+        &[
+            method::window::SHOW,
+            method::window::READY_TO_SHOW,
+            method::window::HIDE,
+            method::window::SET_GEOMETRY,
+            method::window::GET_GEOMETRY,
+            method::window::SUPPORTED_GEOMETRY,
+            method::window::SET_STYLE,
+            method::window::SET_TITLE,
+            method::window::SET_STATE,
+            method::window::GET_STATE,
+            method::window::ACTIVATE,
+        ]
+    }
+
+    fn set_visible(&self, visible: bool) {
+        if self.visible.replace(visible) != visible {
+            if let Some(delegate) = self.delegate.upgrade() {
+                delegate.visibility_changed(visible);
+            }
+        }
+    }
+
+    pub fn title(&self) -> String {
+        self.title.borrow().clone()
+    }
+
+    pub fn style(&self) -> WindowStyle {
+        self.style.borrow().clone()
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible.get()
+    }
+
     pub fn show(&self) -> PlatformResult<()> {
-        Err(PlatformError::NotImplemented)
+        if self.ready_to_show.get() {
+            self.set_visible(true);
+        } else {
+            self.show_when_ready.set(true);
+        }
+        Ok(())
     }
 
     pub fn ready_to_show(&self) -> PlatformResult<()> {
-        Err(PlatformError::NotImplemented)
+        self.ready_to_show.set(true);
+        if self.show_when_ready.get() {
+            self.set_visible(true);
+        }
+        Ok(())
     }
 
     pub fn close(&self) -> PlatformResult<()> {
@@ -54,7 +137,12 @@ impl PlatformWindow {
     }
 
     pub fn hide(&self) -> PlatformResult<()> {
-        Err(PlatformError::NotImplemented)
+        if self.ready_to_show.get() {
+            self.set_visible(false);
+        } else {
+            self.show_when_ready.set(false);
+        }
+        Ok(())
     }
 
     pub fn show_modal<F>(&self, done_callback: F)
@@ -64,23 +152,94 @@ impl PlatformWindow {
         done_callback(Err(PlatformError::NotImplemented))
     }
 
+    pub fn set_title(&self, title: &str) -> PlatformResult<()> {
+        self.title.replace(title.into());
+        Ok(())
+    }
+
     pub fn set_geometry(
         &self,
         geometry: WindowGeometryRequest,
     ) -> PlatformResult<WindowGeometryFlags> {
-        Err(PlatformError::NotImplemented)
+        let request = geometry.filtered_by_preference();
+        let mut res = WindowGeometryFlags::default();
+        let geometry = {
+            let mut geometry = self.geometry.borrow_mut();
+            macro_rules! apply {
+                ($field:ident) => {
+                    if let Some(value) = request.$field {
+                        geometry.$field = Some(value);
+                        res.$field = true;
+                    }
+                };
+            }
+            apply!(frame_origin);
+            apply!(frame_size);
+            apply!(content_origin);
+            apply!(content_size);
+            apply!(min_frame_size);
+            apply!(max_frame_size);
+            apply!(min_content_size);
+            apply!(max_content_size);
+            geometry.clone()
+        };
+        if let Some(delegate) = self.delegate.upgrade() {
+            delegate.geometry_changed(geometry);
+        }
+        Ok(res)
     }
 
     pub fn get_geometry(&self) -> PlatformResult<WindowGeometry> {
-        Err(PlatformError::NotImplemented)
+        Ok(self.geometry.borrow().clone())
     }
 
     pub fn supported_geometry(&self) -> PlatformResult<WindowGeometryFlags> {
-        Err(PlatformError::NotImplemented)
+        Ok(WindowGeometryFlags {
+            frame_origin: true,
+            frame_size: true,
+            content_origin: true,
+            content_size: true,
+            min_frame_size: true,
+            max_frame_size: true,
+            min_content_size: true,
+            max_content_size: true,
+        })
     }
 
     pub fn set_style(&self, style: WindowStyle) -> PlatformResult<()> {
-        Err(PlatformError::NotImplemented)
+        self.style.replace(style);
+        Ok(())
+    }
+
+    pub fn set_state(&self, state: WindowState) -> PlatformResult<()> {
+        if self.state.replace(state) != state {
+            if let Some(delegate) = self.delegate.upgrade() {
+                delegate.state_changed(state);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_state(&self) -> PlatformResult<WindowState> {
+        Ok(self.state.get())
+    }
+
+    pub fn is_active(&self) -> bool {
+        ACTIVE_WINDOW.with(|w| std::ptr::eq(w.borrow().as_ptr(), self))
+    }
+
+    pub fn activate(&self) -> PlatformResult<()> {
+        if self.is_active() {
+            return Ok(());
+        }
+        let previous = ACTIVE_WINDOW.with(|w| w.replace(self.weak_self.clone_value()));
+        if let Some(delegate) = previous.upgrade().and_then(|w| w.delegate.upgrade()) {
+            delegate.activation_changed(false);
+        }
+        if let Some(delegate) = self.delegate.upgrade() {
+            delegate.activation_changed(true);
+        }
+        Ok(())
     }
 
     pub fn perform_window_drag(&self) -> PlatformResult<()> {
@@ -112,3 +271,32 @@ impl PlatformWindow {
         Err(PlatformError::NotImplemented)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        codec::Value,
+        shell::{structs::WindowCreateOptions, Context},
+    };
+
+    #[test]
+    fn test_records_title() {
+        let context = Context::new(Default::default()).unwrap();
+        let mut window_manager = context.window_manager.borrow_mut();
+        let handle = window_manager.create_window_with_options(
+            Value::Null,
+            None,
+            WindowCreateOptions {
+                title: Some("Initial".into()),
+                ..Default::default()
+            },
+        );
+        let window = window_manager.get_window(handle).unwrap();
+        drop(window_manager);
+        let platform_window = window.platform_window.borrow().clone();
+        assert_eq!(platform_window.title(), "Initial");
+
+        window.set_title("Updated").unwrap();
+        assert_eq!(platform_window.title(), "Updated");
+    }
+}
@@ -1,6 +1,4 @@
-use std::{mem::size_of, ptr};
-
-use crate::shell::platform::key_interceptor::override_key_event;
+use std::ptr;
 
 use super::{
     binary_messenger::PlatformBinaryMessenger,
@@ -30,12 +28,6 @@ impl PlatformEngine {
         };
 
         let engine = unsafe { FlutterDesktopEngineCreate(&properties) };
-
-        unsafe {
-            // TODO: This makes assumption about internal engine layout and will possibly
-            // break in future;
-            override_key_event((engine as *mut u8).add(2 * size_of::<isize>()) as *mut _);
-        }
         Self { handle: engine }
     }
 
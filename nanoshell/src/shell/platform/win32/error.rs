@@ -1,6 +1,10 @@
 use super::util::hresult_description;
 use std::fmt::Display;
 
+use velcro::hash_map;
+
+use crate::{codec::Value, error_code};
+
 #[derive(Debug, Clone)]
 pub enum PlatformError {
     UnknownError,
@@ -12,6 +16,30 @@ pub enum PlatformError {
 
 pub type PlatformResult<T> = Result<T, PlatformError>;
 
+impl PlatformError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            PlatformError::UnknownError => error_code::PLATFORM_UNKNOWN_ERROR,
+            PlatformError::LaunchEngineFailure => error_code::PLATFORM_LAUNCH_ENGINE_FAILURE,
+            PlatformError::SendMessageFailure { .. } => error_code::PLATFORM_SEND_MESSAGE_FAILURE,
+            PlatformError::HResult(_) => error_code::PLATFORM_HRESULT,
+            PlatformError::NotAvailable => error_code::PLATFORM_NOT_AVAILABLE,
+        }
+    }
+
+    pub fn details(&self) -> Value {
+        match self {
+            PlatformError::SendMessageFailure { channel } => Value::Map(hash_map! {
+                "channel".into() : channel.as_str().into(),
+            }),
+            PlatformError::HResult(hresult) => Value::Map(hash_map! {
+                "hresult".into() : (*hresult as i64).into(),
+            }),
+            _ => Value::Null,
+        }
+    }
+}
+
 impl Display for PlatformError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
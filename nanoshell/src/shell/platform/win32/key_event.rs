@@ -12,7 +12,11 @@ struct KeyEvent {
 }
 
 pub fn process_key_event(data: Vec<u8>) -> Vec<u8> {
-    let mut event: KeyEvent = serde_json::from_slice(&data).unwrap();
+    // Message comes from Dart, pass it on unchanged if it's not a key event
+    let mut event: KeyEvent = match serde_json::from_slice(&data) {
+        Ok(event) => event,
+        Err(_) => return data,
+    };
 
     let key_state = &mut [0u8; 256];
     let buf = &mut [0u16, 10];
@@ -8,16 +8,17 @@ use std::{
 use crate::{
     codec::Value,
     shell::{
+        constants::method,
         structs::{
             DragEffect, DragRequest, PopupMenuRequest, PopupMenuResponse, WindowGeometry,
-            WindowGeometryFlags, WindowGeometryRequest, WindowStyle,
+            WindowGeometryFlags, WindowGeometryRequest, WindowState, WindowStyle,
         },
         Context, IPoint, PlatformWindowDelegate, Point,
     },
-    util::LateRefCell,
+    util::{LateRefCell, OkLog},
 };
 
-use super::{all_bindings::*, drag_context::DragContext, dxgi_hook::{set_override_parent_hwnd, take_override_parent_hwnd}, engine::PlatformEngine, error::{PlatformError, PlatformResult}, flutter_api::*, menu::PlatformMenu, window_adapter::{SetWindowLongPtrW, WindowAdapter}, window_base::{WindowBaseState, WindowDelegate}, window_menu::{WindowMenu, WindowMenuDelegate}};
+use super::{all_bindings::*, drag_context::DragContext, dxgi_hook::{set_override_parent_hwnd, take_override_parent_hwnd}, engine::PlatformEngine, error::{PlatformError, PlatformResult}, flutter_api::*, menu::PlatformMenu, util::to_utf16, window_adapter::{SetWindowLongPtrW, WindowAdapter}, window_base::{WindowBaseState, WindowDelegate}, window_menu::{WindowMenu, WindowMenuDelegate}};
 
 pub type PlatformWindowType = HWND;
 
@@ -36,6 +37,7 @@ pub struct PlatformWindow {
     modal_close_callback: RefCell<Option<Box<dyn FnOnce(PlatformResult<Value>) -> ()>>>,
     ready_to_show: Cell<bool>,
     show_when_ready: Cell<bool>,
+    last_state: Cell<WindowState>,
     mouse_state: RefCell<MouseState>,
 }
 
@@ -65,6 +67,7 @@ impl PlatformWindow {
             modal_close_callback: RefCell::new(None),
             ready_to_show: Cell::new(false),
             show_when_ready: Cell::new(false),
+            last_state: Cell::new(WindowState::Normal),
             mouse_state: RefCell::new(MouseState {
                 last_button_down: None,
             }),
@@ -154,7 +157,31 @@ impl WindowAdapter for PlatformWindow {
 }
 
 impl WindowDelegate for PlatformWindow {
-    fn displays_changed(&self) {}
+    fn displays_changed(&self) {
+        self.context.display_manager.borrow().displays_changed();
+    }
+
+    fn state_changed(&self) {
+        let state = self.state.borrow().get_state();
+        if self.last_state.replace(state) != state {
+            if let Some(delegate) = self.delegate.upgrade() {
+                delegate.state_changed(state);
+            }
+        }
+    }
+
+    fn activation_changed(&self, active: bool) {
+        if let Some(delegate) = self.delegate.upgrade() {
+            delegate.activation_changed(active);
+        }
+    }
+
+    fn geometry_changed(&self) {
+        let geometry = self.state.borrow().get_geometry().ok_log();
+        if let (Some(delegate), Some(geometry)) = (self.delegate.upgrade(), geometry) {
+            delegate.geometry_changed(geometry);
+        }
+    }
 
     fn should_close(&self) {
         let u = self.delegate.upgrade();
@@ -206,6 +233,31 @@ impl PlatformWindow {
         })
     }
 
+    // Window methods implemented by this backend; Reported to Dart during init
+    pub fn supported_methods() -> &'static [&'static str] {
+        &[
+            method::window::SHOW,
+            method::window::SHOW_MODAL,
+            method::window::READY_TO_SHOW,
+            method::window::HIDE,
+            method::window::CLOSE,
+            method::window::CLOSE_WITH_RESULT,
+            method::window::SET_GEOMETRY,
+            method::window::GET_GEOMETRY,
+            method::window::SUPPORTED_GEOMETRY,
+            method::window::SET_STYLE,
+            method::window::SET_TITLE,
+            method::window::SET_STATE,
+            method::window::GET_STATE,
+            method::window::ACTIVATE,
+            method::window::PERFORM_WINDOW_DRAG,
+            method::window::SHOW_POPUP_MENU,
+            method::window::HIDE_POPUP_MENU,
+            method::window::SHOW_SYSTEM_MENU,
+            method::drag_source::BEGIN_DRAG_SESSION,
+        ]
+    }
+
     pub fn show(&self) -> PlatformResult<()> {
         if self.ready_to_show.get() {
             self.show_internal()
@@ -271,12 +323,35 @@ impl PlatformWindow {
         self.state.borrow().supported_geometry()
     }
 
+    pub fn set_title(&self, title: &str) -> PlatformResult<()> {
+        let title = to_utf16(title);
+        unsafe {
+            SetWindowTextW(self.hwnd(), title.as_ptr());
+        }
+        Ok(())
+    }
+
     pub fn set_style(&self, style: WindowStyle) -> PlatformResult<()> {
         self.state.borrow().set_style(style)?;
         self.force_redraw();
         Ok(())
     }
 
+    pub fn set_state(&self, state: WindowState) -> PlatformResult<()> {
+        self.state.borrow().set_state(state)
+    }
+
+    pub fn get_state(&self) -> PlatformResult<WindowState> {
+        Ok(self.state.borrow().get_state())
+    }
+
+    pub fn activate(&self) -> PlatformResult<()> {
+        unsafe {
+            SetForegroundWindow(self.hwnd());
+        }
+        Ok(())
+    }
+
     pub fn perform_window_drag(&self) -> PlatformResult<()> {
         self.state.borrow().perform_window_drag()
     }
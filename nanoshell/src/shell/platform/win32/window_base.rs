@@ -6,7 +6,8 @@ use std::{
 use crate::{
     shell::{
         structs::{
-            WindowFrame, WindowGeometry, WindowGeometryFlags, WindowGeometryRequest, WindowStyle,
+            WindowFrame, WindowGeometry, WindowGeometryFlags, WindowGeometryRequest, WindowLevel,
+            WindowState, WindowStyle,
         },
         IPoint, IRect, ISize, Point, Rect, Size,
     },
@@ -16,9 +17,9 @@ use crate::{
 use super::{
     all_bindings::*,
     display::Displays,
-    error::PlatformResult,
+    error::{PlatformError, PlatformResult},
     flutter_api::{FlutterDesktopGetDpiForHWND, FlutterDesktopGetDpiForMonitor},
-    util::{clamp, BoolResultExt, ErrorCodeExt, GET_X_LPARAM, GET_Y_LPARAM},
+    util::{clamp, BoolResultExt, ErrorCodeExt, GET_X_LPARAM, GET_Y_LPARAM, LOWORD},
 };
 
 pub struct WindowBaseState {
@@ -29,6 +30,13 @@ pub struct WindowBaseState {
     max_content_size: RefCell<Size>,
     delegate: Weak<dyn WindowDelegate>,
     style: RefCell<WindowStyle>,
+    full_screen: RefCell<Option<FullScreenRestore>>,
+}
+
+// Window style and frame to restore after leaving full screen
+struct FullScreenRestore {
+    style: i32,
+    rect: RECT,
 }
 
 const LARGE_SIZE: f64 = 64.0 * 1024.0;
@@ -43,6 +51,7 @@ impl WindowBaseState {
             min_content_size: RefCell::new(Size::wh(0.0, 0.0)),
             max_content_size: RefCell::new(Size::wh(LARGE_SIZE, LARGE_SIZE)),
             style: Default::default(),
+            full_screen: RefCell::new(None),
         }
     }
 
@@ -364,17 +373,27 @@ impl WindowBaseState {
     }
 
     pub fn update_dwm_frame(&self) -> PlatformResult<()> {
-        let margin = match self.style.borrow().frame {
-            WindowFrame::Regular => 0, // already has shadow
-            WindowFrame::NoTitle => 1, // neede for window shadow
-            WindowFrame::NoFrame => 0, // neede for transparency
-        };
-
-        let margins = MARGINS {
-            cx_left_width: 0,
-            cx_right_width: 0,
-            cy_top_height: margin,
-            cy_bottom_height: 0,
+        let style = self.style.borrow();
+        let margins = if style.frame == WindowFrame::NoFrame && style.transparent {
+            // extend the frame into entire client area so that it is not painted
+            MARGINS {
+                cx_left_width: -1,
+                cx_right_width: -1,
+                cy_top_height: -1,
+                cy_bottom_height: -1,
+            }
+        } else {
+            let margin = match style.frame {
+                WindowFrame::Regular => 0, // already has shadow
+                WindowFrame::NoTitle => 1, // neede for window shadow
+                WindowFrame::NoFrame => 0, // neede for transparency
+            };
+            MARGINS {
+                cx_left_width: 0,
+                cx_right_width: 0,
+                cy_top_height: margin,
+                cy_bottom_height: 0,
+            }
         };
         unsafe {
             DwmExtendFrameIntoClientArea(self.hwnd, &margins as *const _).as_platform_result()
@@ -382,6 +401,11 @@ impl WindowBaseState {
     }
 
     pub fn set_style(&self, style: WindowStyle) -> PlatformResult<()> {
+        // Layered windows need redirection surface, which direct composition
+        // windows are created without
+        if style.opacity < 1.0 && !self.has_redirection_surface() {
+            return Err(PlatformError::NotAvailable);
+        }
         *self.style.borrow_mut() = style.clone();
         unsafe {
             let mut s = GetWindowLongW(self.hwnd, GWL_STYLE) as u32;
@@ -417,6 +441,31 @@ impl WindowBaseState {
             }
 
             SetWindowLongW(self.hwnd, GWL_STYLE, s as i32);
+
+            let mut ex = GetWindowLongW(self.hwnd, GWL_EXSTYLE);
+            // Taskbar only picks up the change when the window is shown again
+            if style.skip_taskbar {
+                ex = (ex | WS_EX_TOOLWINDOW) & !WS_EX_APPWINDOW;
+            } else {
+                ex = (ex | WS_EX_APPWINDOW) & !WS_EX_TOOLWINDOW;
+            }
+            let layered = style.opacity < 1.0;
+            if layered {
+                ex |= WS_EX_LAYERED;
+            } else {
+                ex &= !WS_EX_LAYERED;
+            }
+            SetWindowLongW(self.hwnd, GWL_EXSTYLE, ex);
+            if layered {
+                SetLayeredWindowAttributes(
+                    self.hwnd,
+                    0,
+                    (style.opacity * 255.0).round() as u8,
+                    LWA_ALPHA as u32,
+                )
+                .as_platform_result()?;
+            }
+
             SetWindowPos(
                 self.hwnd,
                 HWND(0),
@@ -428,11 +477,120 @@ impl WindowBaseState {
             )
             .as_platform_result()?;
 
+            // HWND_TOPMOST and HWND_NOTOPMOST
+            let insert_after = match style.level {
+                WindowLevel::Normal => HWND(-2),
+                WindowLevel::Floating | WindowLevel::ModalPanel => HWND(-1),
+            };
+            SetWindowPos(
+                self.hwnd,
+                insert_after,
+                0,
+                0,
+                0,
+                0,
+                (SWP_NOACTIVATE | SWP_NOMOVE | SWP_NOSIZE) as u32,
+            )
+            .as_platform_result()?;
+
             self.update_dwm_frame()?;
         }
         Ok(())
     }
 
+    pub fn get_state(&self) -> WindowState {
+        unsafe {
+            if self.full_screen.borrow().is_some() {
+                WindowState::FullScreen
+            } else if IsIconic(self.hwnd) == TRUE {
+                WindowState::Minimized
+            } else if IsZoomed(self.hwnd) == TRUE {
+                WindowState::Maximized
+            } else {
+                WindowState::Normal
+            }
+        }
+    }
+
+    pub fn set_state(&self, state: WindowState) -> PlatformResult<()> {
+        if self.get_state() == state {
+            return Ok(());
+        }
+        if state != WindowState::FullScreen {
+            self.exit_full_screen()?;
+        }
+        unsafe {
+            match state {
+                WindowState::Normal => {
+                    ShowWindow(self.hwnd, SW_RESTORE);
+                }
+                WindowState::Minimized => {
+                    ShowWindow(self.hwnd, SW_MINIMIZE);
+                }
+                WindowState::Maximized => {
+                    ShowWindow(self.hwnd, SW_MAXIMIZE);
+                }
+                WindowState::FullScreen => self.enter_full_screen()?,
+            }
+        }
+        self.delegate().state_changed();
+        Ok(())
+    }
+
+    unsafe fn enter_full_screen(&self) -> PlatformResult<()> {
+        if IsIconic(self.hwnd) == TRUE || IsZoomed(self.hwnd) == TRUE {
+            ShowWindow(self.hwnd, SW_RESTORE);
+        }
+        let mut rect: RECT = Default::default();
+        GetWindowRect(self.hwnd, &mut rect as *mut _).as_platform_result()?;
+
+        let monitor = MonitorFromWindow(self.hwnd, MONITOR_FROM_FLAGS::MONITOR_DEFAULTTONEAREST);
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        GetMonitorInfoW(monitor, &mut info as *mut _).as_platform_result()?;
+
+        let style = GetWindowLongW(self.hwnd, GWL_STYLE);
+        self.full_screen
+            .replace(Some(FullScreenRestore { style, rect }));
+
+        let s = (style as u32 & !(WS_OVERLAPPEDWINDOW | WS_DLGFRAME)) | WS_POPUP;
+        SetWindowLongW(self.hwnd, GWL_STYLE, s as i32);
+        let r = info.rcMonitor;
+        SetWindowPos(
+            self.hwnd,
+            HWND(0),
+            r.left,
+            r.top,
+            r.right - r.left,
+            r.bottom - r.top,
+            (SWP_FRAMECHANGED | SWP_NOACTIVATE | SWP_NOZORDER) as u32,
+        )
+        .as_platform_result()
+    }
+
+    fn exit_full_screen(&self) -> PlatformResult<()> {
+        let restore = self.full_screen.borrow_mut().take();
+        if let Some(restore) = restore {
+            unsafe {
+                SetWindowLongW(self.hwnd, GWL_STYLE, restore.style);
+                let r = restore.rect;
+                SetWindowPos(
+                    self.hwnd,
+                    HWND(0),
+                    r.left,
+                    r.top,
+                    r.right - r.left,
+                    r.bottom - r.top,
+                    (SWP_FRAMECHANGED | SWP_NOACTIVATE | SWP_NOZORDER) as u32,
+                )
+                .as_platform_result()?;
+            }
+        }
+        Ok(())
+    }
+
     pub fn perform_window_drag(&self) -> PlatformResult<()> {
         unsafe {
             println!("Perform window drag!");
@@ -489,7 +647,7 @@ impl WindowBaseState {
         &self,
         _h_wnd: HWND,
         msg: u32,
-        _w_param: WPARAM,
+        w_param: WPARAM,
         l_param: LPARAM,
     ) -> Option<LRESULT> {
         match msg as i32 {
@@ -501,6 +659,21 @@ impl WindowBaseState {
                 self.delegate().will_close();
                 None
             }
+            WM_SIZE => {
+                // minimized, maximized or restored
+                self.delegate().state_changed();
+                self.delegate().geometry_changed();
+                None
+            }
+            WM_MOVE => {
+                self.delegate().geometry_changed();
+                None
+            }
+            WM_ACTIVATE => {
+                let active = LOWORD(w_param.0 as u32) as u32 != WA_INACTIVE;
+                self.delegate().activation_changed(active);
+                None
+            }
             WM_DISPLAYCHANGE => {
                 Displays::displays_changed();
                 self.delegate().displays_changed();
@@ -563,4 +736,7 @@ pub trait WindowDelegate {
     fn should_close(&self);
     fn will_close(&self);
     fn displays_changed(&self);
+    fn state_changed(&self);
+    fn activation_changed(&self, active: bool);
+    fn geometry_changed(&self);
 }
@@ -0,0 +1,199 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    hash::Hash,
+    rc::Rc,
+    time::Duration,
+};
+
+use super::{Context, ScheduledCallback};
+
+// Collects replies from multiple targets and delivers them to single callback
+// once every target replied or the timeout expired. Targets that did not reply
+// in time get result produced by on_timeout. The callback is always invoked
+// on run loop (never synchronously from reply), so it is safe to use managers
+// from within the callback.
+pub(super) struct ReplyAggregator<K, R>
+where
+    K: 'static,
+    R: 'static,
+{
+    state: Rc<RefCell<Option<AggregatorState<K, R>>>>,
+}
+
+struct AggregatorState<K, R> {
+    context: Rc<Context>,
+    pending: HashSet<K>,
+    results: HashMap<K, R>,
+    on_timeout: Box<dyn Fn(&K) -> R>,
+    callback: Box<dyn FnOnce(HashMap<K, R>)>,
+    timeout: Option<ScheduledCallback>,
+}
+
+impl<K, R> Clone for ReplyAggregator<K, R> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<K, R> ReplyAggregator<K, R>
+where
+    K: Hash + Eq + Clone + 'static,
+    R: 'static,
+{
+    pub fn new<T, F>(
+        context: Rc<Context>,
+        targets: Vec<K>,
+        timeout: Duration,
+        on_timeout: T,
+        callback: F,
+    ) -> Self
+    where
+        T: Fn(&K) -> R + 'static,
+        F: FnOnce(HashMap<K, R>) + 'static,
+    {
+        let res = Self {
+            state: Rc::new(RefCell::new(None)),
+        };
+        let empty = targets.is_empty();
+        let state = res.state.clone();
+        let timeout = context.run_loop.borrow().schedule(
+            move || Self::finish(state),
+            if empty {
+                Duration::from_secs(0)
+            } else {
+                timeout
+            },
+        );
+        res.state.borrow_mut().replace(AggregatorState {
+            context,
+            pending: targets.into_iter().collect(),
+            results: HashMap::new(),
+            on_timeout: Box::new(on_timeout),
+            callback: Box::new(callback),
+            timeout: Some(timeout),
+        });
+        res
+    }
+
+    pub fn reply(&self, target: K, result: R) {
+        let done = {
+            let mut state = self.state.borrow_mut();
+            match state.as_mut() {
+                Some(state) => {
+                    let removed = state.pending.remove(&target);
+                    if removed {
+                        state.results.insert(target, result);
+                    }
+                    removed && state.pending.is_empty()
+                }
+                None => false,
+            }
+        };
+        if done {
+            // replace timeout with callback on next run loop turn
+            let state = self.state.clone();
+            let mut s = self.state.borrow_mut();
+            if let Some(s) = s.as_mut() {
+                let finish = s
+                    .context
+                    .run_loop
+                    .borrow()
+                    .schedule(move || Self::finish(state), Duration::from_secs(0));
+                s.timeout.replace(finish);
+            }
+        }
+    }
+
+    fn finish(state: Rc<RefCell<Option<AggregatorState<K, R>>>>) {
+        let state = state.borrow_mut().take();
+        if let Some(mut state) = state {
+            if let Some(mut timeout) = state.timeout.take() {
+                // currently executing
+                timeout.detach();
+            }
+            for target in state.pending.drain() {
+                let result = (state.on_timeout)(&target);
+                state.results.insert(target, result);
+            }
+            (state.callback)(state.results);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::HashMap, rc::Rc, time::Duration};
+
+    use crate::shell::Context;
+
+    use super::ReplyAggregator;
+
+    type Replies = Rc<RefCell<Option<HashMap<i32, &'static str>>>>;
+
+    fn aggregator(
+        context: &Rc<Context>,
+        targets: Vec<i32>,
+    ) -> (ReplyAggregator<i32, &'static str>, Replies) {
+        let replies = Rc::new(RefCell::new(None));
+        let replies_copy = replies.clone();
+        let aggregator = ReplyAggregator::new(
+            context.clone(),
+            targets,
+            Duration::from_secs(1),
+            |_| "timeout",
+            move |r| {
+                replies_copy.replace(Some(r));
+            },
+        );
+        (aggregator, replies)
+    }
+
+    #[test]
+    fn test_all_replied() {
+        let context = Context::new(Default::default()).unwrap();
+        let (aggregator, replies) = aggregator(&context, vec![1, 2]);
+        aggregator.reply(1, "first");
+        aggregator.reply(2, "second");
+        // unknown and repeated replies are ignored
+        aggregator.reply(3, "third");
+        aggregator.reply(1, "again");
+        assert!(replies.borrow().is_none());
+
+        let run_loop = context.run_loop.borrow().clone();
+        run_loop.run();
+        let replies = replies.borrow_mut().take().unwrap();
+        assert_eq!(replies.len(), 2);
+        assert_eq!(replies[&1], "first");
+        assert_eq!(replies[&2], "second");
+    }
+
+    #[test]
+    fn test_partial_timeout() {
+        let context = Context::new(Default::default()).unwrap();
+        let (aggregator, replies) = aggregator(&context, vec![1, 2]);
+        aggregator.reply(1, "first");
+
+        let run_loop = context.run_loop.borrow().clone();
+        run_loop.run();
+        let replies = replies.borrow_mut().take().unwrap();
+        assert_eq!(replies[&1], "first");
+        assert_eq!(replies[&2], "timeout");
+
+        // late reply after the callback was invoked
+        aggregator.reply(2, "second");
+    }
+
+    #[test]
+    fn test_empty_targets() {
+        let context = Context::new(Default::default()).unwrap();
+        let (_, replies) = aggregator(&context, Vec::new());
+        assert!(replies.borrow().is_none());
+
+        let run_loop = context.run_loop.borrow().clone();
+        run_loop.run();
+        assert_eq!(replies.borrow_mut().take(), Some(HashMap::new()));
+    }
+}
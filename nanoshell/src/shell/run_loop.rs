@@ -1,4 +1,6 @@
-use std::{rc::Rc, time::Duration};
+use std::{cell::Cell, rc::Rc, time::Duration};
+
+use crate::util::trace;
 
 use super::platform::run_loop::{
     HandleType, PlatformRunLoop, PlatformRunLoopSender, INVALID_HANDLE,
@@ -7,12 +9,14 @@ use super::platform::run_loop::{
 pub struct ScheduledCallback {
     platform_run_loop: Rc<PlatformRunLoop>,
     handle: HandleType,
+    pending: PendingCallback,
 }
 
 impl ScheduledCallback {
     pub fn cancel(&mut self) {
         if self.handle != INVALID_HANDLE {
             self.platform_run_loop.unschedule(self.handle);
+            self.pending.finish();
 
             self.handle = INVALID_HANDLE;
         }
@@ -29,14 +33,41 @@ impl Drop for ScheduledCallback {
     }
 }
 
+// Keeps count of scheduled callbacks that have neither run nor been cancelled
+#[derive(Clone)]
+struct PendingCallback {
+    finished: Rc<Cell<bool>>,
+    pending_count: Rc<Cell<usize>>,
+}
+
+impl PendingCallback {
+    fn new(pending_count: Rc<Cell<usize>>) -> Self {
+        pending_count.set(pending_count.get() + 1);
+        Self {
+            finished: Rc::new(Cell::new(false)),
+            pending_count,
+        }
+    }
+
+    fn finish(&self) {
+        if !self.finished.replace(true) {
+            self.pending_count.set(self.pending_count.get() - 1);
+        }
+    }
+}
+
+// Cloned RunLoop refers to the same underlying platform run loop
+#[derive(Clone)]
 pub struct RunLoop {
     platform_run_loop: Rc<PlatformRunLoop>,
+    pending_count: Rc<Cell<usize>>,
 }
 
 impl RunLoop {
     pub fn new() -> Self {
         Self {
             platform_run_loop: Rc::new(PlatformRunLoop::new()),
+            pending_count: Rc::new(Cell::new(0)),
         }
     }
 
@@ -45,12 +76,26 @@ impl RunLoop {
     where
         F: FnOnce() -> () + 'static,
     {
+        let pending = PendingCallback::new(self.pending_count.clone());
+        let pending_copy = pending.clone();
+        let callback = move || {
+            pending_copy.finish();
+            let span = trace::run_loop_callback_span();
+            let _guard = span.enter();
+            callback();
+        };
         ScheduledCallback {
             platform_run_loop: self.platform_run_loop.clone(),
             handle: self.platform_run_loop.schedule(callback, in_time),
+            pending,
         }
     }
 
+    // Number of scheduled callbacks that have not been executed or cancelled yet
+    pub fn pending_callbacks(&self) -> usize {
+        self.pending_count.get()
+    }
+
     pub fn run(&self) {
         self.platform_run_loop.run()
     }
@@ -11,7 +11,7 @@ pub enum GeometryPreference {
     PreferContent,
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct WindowGeometry {
     pub frame_origin: Option<Point>,
@@ -30,6 +30,10 @@ pub struct WindowGeometry {
 pub struct WindowGeometryRequest {
     pub geometry: WindowGeometry,
     pub preference: GeometryPreference,
+
+    // Applied after geometry; Moves the window while keeping its frame size
+    #[serde(default)]
+    pub placement: Option<WindowPlacement>,
 }
 
 impl WindowGeometryRequest {
@@ -88,6 +92,34 @@ impl Default for WindowGeometry {
     }
 }
 
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum WindowPlacementPolicy {
+    // Center over parent window; Windows without parent are centered on display
+    CenterOnParent,
+
+    // Center in work area of display with parent window (or the window itself)
+    CenterOnDisplay,
+
+    // Offset from the most recently created window
+    Cascade,
+
+    // Below anchor rect (or above if there is not enough space), i.e. for popups
+    Anchor,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WindowPlacement {
+    pub policy: Option<WindowPlacementPolicy>,
+
+    // Required for Anchor policy; Logical screen coordinates
+    pub anchor: Option<Rect>,
+
+    // Move (and shrink if necessary) the frame to fit display work area
+    pub constrain_to_work_area: bool,
+}
+
 #[derive(serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct PopupMenuRequest {
@@ -206,7 +238,20 @@ impl Default for WindowFrame {
     }
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum WindowLevel {
+    #[default]
+    Normal,
+
+    // Always on top of normal windows
+    Floating,
+
+    // Above floating windows; Same as Floating on Windows
+    ModalPanel,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct WindowStyle {
     pub frame: WindowFrame,
@@ -215,6 +260,99 @@ pub struct WindowStyle {
     pub can_minimize: bool,
     pub can_maximize: bool,
     pub can_full_screen: bool,
+
+    #[serde(default)]
+    pub level: WindowLevel,
+
+    // Hide window from taskbar (Windows); On macOS the dock icon belongs to the
+    // application so the window is only excluded from Window menu and window cycling
+    #[serde(default)]
+    pub skip_taskbar: bool,
+
+    // 0.0 (fully transparent) to 1.0 (opaque); On Windows opacity below 1.0 is
+    // not available for windows using direct composition
+    #[serde(default = "default_opacity")]
+    pub opacity: f64,
+
+    // Frameless windows only; Window background is not painted so that areas
+    // left transparent by Flutter show what is behind the window
+    #[serde(default)]
+    pub transparent: bool,
+}
+
+fn default_opacity() -> f64 {
+    1.0
+}
+
+impl WindowStyle {
+    // Opacity clamped to 0.0 - 1.0; Invalid value makes the window opaque
+    pub(super) fn normalized(mut self) -> Self {
+        self.opacity = if self.opacity.is_nan() {
+            default_opacity()
+        } else {
+            self.opacity.clamp(0.0, 1.0)
+        };
+        self
+    }
+}
+
+impl Default for WindowStyle {
+    fn default() -> Self {
+        Self {
+            frame: Default::default(),
+            can_resize: false,
+            can_close: false,
+            can_minimize: false,
+            can_maximize: false,
+            can_full_screen: false,
+            level: Default::default(),
+            skip_taskbar: false,
+            opacity: default_opacity(),
+            transparent: false,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum WindowState {
+    #[default]
+    Normal,
+    Minimized,
+    Maximized,
+    FullScreen,
+}
+
+// Applied to the window before it is first shown
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WindowCreateOptions {
+    pub title: Option<String>,
+    pub style: Option<WindowStyle>,
+    pub geometry: Option<WindowGeometryRequest>,
+
+    // Show the window as soon as it calls ready-to-show, without explicit show call
+    pub show_when_ready: bool,
+
+    // Stable identifier under which window geometry and state are saved and
+    // restored; Requires ContextOptions::window_state_file
+    pub persistence_key: Option<String>,
+}
+
+//
+// Display
+//
+
+// All rects are in logical coordinates with top left origin
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Display {
+    pub id: i64,
+    pub frame: Rect,
+
+    // Frame without taskbar, dock and menu bar
+    pub work_area: Rect,
+    pub scale: f64,
 }
 
 //
@@ -295,3 +433,41 @@ pub struct MenuAction {
 pub struct SetMenuRequest {
     pub handle: MenuHandle,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::codec::{
+        value::{from_value, to_value},
+        Value,
+    };
+
+    use super::{WindowLevel, WindowStyle};
+
+    #[test]
+    fn test_window_style_defaults() {
+        // older clients don't send level, opacity and other newer fields
+        let mut value = to_value(WindowStyle::default()).unwrap();
+        if let Value::Map(map) = &mut value {
+            for key in ["level", "skipTaskbar", "opacity", "transparent"] {
+                map.remove(&Value::String(key.into())).unwrap();
+            }
+        }
+        let style: WindowStyle = from_value(&value).unwrap();
+        assert_eq!(style.level, WindowLevel::Normal);
+        assert!(!style.skip_taskbar);
+        assert_eq!(style.opacity, 1.0);
+        assert!(!style.transparent);
+    }
+
+    #[test]
+    fn test_window_style_normalized() {
+        let with_opacity = |opacity| WindowStyle {
+            opacity,
+            ..Default::default()
+        };
+        assert_eq!(with_opacity(0.5).normalized().opacity, 0.5);
+        assert_eq!(with_opacity(1.5).normalized().opacity, 1.0);
+        assert_eq!(with_opacity(-1.0).normalized().opacity, 0.0);
+        assert_eq!(with_opacity(f64::NAN).normalized().opacity, 1.0);
+    }
+}
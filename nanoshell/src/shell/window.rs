@@ -1,30 +1,40 @@
 use std::{
-    cell::Cell,
+    cell::{Cell, RefCell},
+    collections::VecDeque,
     rc::{Rc, Weak},
+    time::Duration,
 };
 
+use log::warn;
+
 use crate::{
     codec::{
         value::{from_value, to_value},
-        Value,
+        MethodCallError, Value,
     },
     util::{LateRefCell, OkLog},
-    Result,
+    Error, Result,
 };
 
 use super::{
     constants::*,
     platform::window::PlatformWindow,
     structs::{
-        DragEffect, DragRequest, DragResult, DraggingInfo, HidePopupMenuRequest, PopupMenuRequest,
-        PopupMenuResponse, SetMenuRequest, WindowGeometry, WindowGeometryFlags,
-        WindowGeometryRequest, WindowStyle,
+        DragEffect, DragRequest, DragResult, DraggingInfo, GeometryPreference,
+        HidePopupMenuRequest, PopupMenuRequest, PopupMenuResponse, SetMenuRequest,
+        WindowCreateOptions, WindowGeometry, WindowGeometryFlags, WindowGeometryRequest,
+        WindowPlacement, WindowState, WindowStyle,
     },
-    Context, EngineHandle, WindowMethodCallReply, WindowMethodCallResult, WindowMethodInvoker,
+    window_method_channel::invalid_request_error,
+    window_placement::{constrain_to_work_areas, display_for_frame, place_frame},
+    Context, EngineHandle, Rect, SavedWindowState, WindowManager, WindowMethodCallReply,
+    WindowMethodCallResult, WindowMethodInvoker,
 };
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Copy, Clone, Hash, Eq, PartialEq)]
-pub struct WindowHandle(pub(super) i64);
+pub struct WindowHandle(pub(crate) i64);
+
+type OutboundMessage = dyn FnOnce(std::result::Result<(), MethodCallError<Value>>);
 
 pub(super) struct Window {
     context: Rc<Context>,
@@ -34,6 +44,15 @@ pub(super) struct Window {
     pub(super) init_data: Value,
     pub(super) parent: Option<WindowHandle>,
     pub(super) initialized: Cell<bool>,
+    pub(super) visible: Cell<bool>,
+    pub(super) active: Cell<bool>,
+    pub(super) outbound_queue_limit: Cell<usize>,
+    outbound_queue: RefCell<VecDeque<Box<OutboundMessage>>>,
+    // Set when dart side of the window was rejected during initialization
+    init_error: RefCell<Option<MethodCallError<Value>>>,
+    pub(super) geometry_event_interval: Cell<Duration>,
+    pending_geometry: RefCell<Option<WindowGeometry>>,
+    persistence_key: RefCell<Option<String>>,
     weak_self: LateRefCell<Weak<Self>>,
 }
 
@@ -44,6 +63,8 @@ impl Window {
         engine_handle: EngineHandle,
         init_data: Value,
         parent: Option<WindowHandle>,
+        outbound_queue_limit: usize,
+        geometry_event_interval: Duration,
     ) -> Self {
         Self {
             context,
@@ -53,6 +74,14 @@ impl Window {
             init_data,
             parent,
             initialized: Cell::new(false),
+            visible: Cell::new(false),
+            active: Cell::new(false),
+            outbound_queue_limit: Cell::new(outbound_queue_limit),
+            outbound_queue: RefCell::new(VecDeque::new()),
+            init_error: RefCell::new(None),
+            geometry_event_interval: Cell::new(geometry_event_interval),
+            pending_geometry: RefCell::new(None),
+            persistence_key: RefCell::new(None),
             weak_self: LateRefCell::new(),
         }
     }
@@ -61,6 +90,63 @@ impl Window {
         self.weak_self.set(weak_self);
     }
 
+    // Runs the callback immediately if window is initialized, otherwise queues it
+    // until dart side of the window calls method:window:init. Callback gets the error
+    // instead if the initialization was rejected.
+    pub(super) fn send_when_initialized<F>(&self, send: F)
+    where
+        F: FnOnce(std::result::Result<(), MethodCallError<Value>>) + 'static,
+    {
+        if self.initialized.get() {
+            send(Ok(()));
+            return;
+        }
+        let init_error = self.init_error.borrow().clone();
+        if let Some(error) = init_error {
+            send(Err(error));
+            return;
+        }
+        let limit = self.outbound_queue_limit.get();
+        if self.outbound_queue.borrow().len() >= limit {
+            warn!(
+                "Outbound queue of uninitialized window {:?} is full ({} messages); Dropping message",
+                self.window_handle, limit
+            );
+            send(Err(Error::OutboundQueueFull(self.window_handle).into()));
+        } else {
+            self.outbound_queue.borrow_mut().push_back(Box::new(send));
+        }
+    }
+
+    pub(super) fn outbound_queue_len(&self) -> usize {
+        self.outbound_queue.borrow().len()
+    }
+
+    pub(super) fn flush_outbound_queue(&self) {
+        self.drain_outbound_queue(Ok(()));
+    }
+
+    // Fails queued and all future messages with given error
+    pub(super) fn reject_initialization(&self, error: MethodCallError<Value>) {
+        self.init_error.replace(Some(error.clone()));
+        self.drain_outbound_queue(Err(error));
+    }
+
+    // Window is being removed before it was initialized
+    pub(super) fn discard_outbound_queue(&self) {
+        self.reject_initialization(Error::InvalidWindowHandle(self.window_handle).into());
+    }
+
+    fn drain_outbound_queue(&self, result: std::result::Result<(), MethodCallError<Value>>) {
+        loop {
+            let send = self.outbound_queue.borrow_mut().pop_front();
+            match send {
+                Some(send) => send(result.clone()),
+                None => break,
+            }
+        }
+    }
+
     // fn invoke_method<F>(&self, method: &str, arg: Value, reply: F)
     // where
     //     F: FnOnce(Result<Value, PlatformError>) -> () + 'static,
@@ -80,6 +166,13 @@ impl Window {
         broadcaster.broadcast_message(message, arguments);
     }
 
+    fn flush_geometry_changed(&self) {
+        let geometry = self.pending_geometry.borrow_mut().take();
+        if let Some(geometry) = geometry {
+            self.broadcast_message(event::window::GEOMETRY_CHANGED, to_value(geometry).unwrap());
+        }
+    }
+
     fn drop_target_invoker(&self) -> WindowMethodInvoker {
         self.context
             .window_method_channel
@@ -108,7 +201,7 @@ impl Window {
         self.platform_window.borrow().clone()
     }
 
-    fn show(&self) -> Result<()> {
+    pub(super) fn show(&self) -> Result<()> {
         self.platform_window().show().map_err(|e| e.into())
     }
 
@@ -116,7 +209,7 @@ impl Window {
         self.platform_window().ready_to_show().map_err(|e| e.into())
     }
 
-    fn close(&self) -> Result<()> {
+    pub(super) fn close(&self) -> Result<()> {
         self.platform_window().close().map_err(|e| e.into())
     }
 
@@ -126,32 +219,203 @@ impl Window {
             .map_err(|e| e.into())
     }
 
-    fn hide(&self) -> Result<()> {
+    pub(super) fn hide(&self) -> Result<()> {
         self.platform_window().hide().map_err(|e| e.into())
     }
 
-    fn set_geometry(&self, geometry: WindowGeometryRequest) -> Result<WindowGeometryFlags> {
-        self.platform_window()
-            .set_geometry(geometry)
-            .map_err(|e| e.into())
+    pub(super) fn set_geometry(
+        &self,
+        mut geometry: WindowGeometryRequest,
+    ) -> Result<WindowGeometryFlags> {
+        let placement = geometry.placement.take();
+        let res = self.platform_window().set_geometry(geometry)?;
+        if let Some(placement) = placement {
+            let (parent, last) = self
+                .context
+                .window_manager
+                .borrow()
+                .related_frames(self.window_handle);
+            self.place(&placement, parent, last)?;
+        }
+        Ok(res)
     }
 
-    fn get_geometry(&self) -> Result<WindowGeometry> {
+    pub(super) fn get_geometry(&self) -> Result<WindowGeometry> {
         self.platform_window().get_geometry().map_err(|e| e.into())
     }
 
+    pub(super) fn frame(&self) -> Option<Rect> {
+        let geometry = self.get_geometry().ok()?;
+        Some(Rect::origin_size(
+            &geometry.frame_origin?,
+            &geometry.frame_size?,
+        ))
+    }
+
+    // Moves the window according to placement; Parent and last window frames are
+    // provided by window manager
+    fn place(
+        &self,
+        placement: &WindowPlacement,
+        parent: Option<Rect>,
+        last: Option<Rect>,
+    ) -> Result<()> {
+        let frame = match self.frame() {
+            Some(frame) => frame,
+            None => return Ok(()),
+        };
+        let displays = self.context.display_manager.borrow().get_displays();
+        let placed = place_frame(&frame, placement, &displays, parent.as_ref(), last.as_ref());
+        if placed != frame {
+            self.platform_window().set_geometry(WindowGeometryRequest {
+                geometry: WindowGeometry {
+                    frame_origin: Some(placed.origin()),
+                    frame_size: Some(placed.size()),
+                    ..Default::default()
+                },
+                preference: GeometryPreference::PreferFrame,
+                placement: None,
+            })?;
+        }
+        Ok(())
+    }
+
     fn supported_geometry(&self) -> Result<WindowGeometryFlags> {
         self.platform_window()
             .supported_geometry()
             .map_err(|e| e.into())
     }
 
-    fn set_style(&self, style: WindowStyle) -> Result<()> {
+    pub(super) fn set_title(&self, title: &str) -> Result<()> {
+        self.platform_window()
+            .set_title(title)
+            .map_err(|e| e.into())
+    }
+
+    // Called after platform window is created, before engine is launched; Window
+    // manager is mutably borrowed at this point so it is passed in for placement
+    pub(super) fn apply_create_options(
+        &self,
+        options: WindowCreateOptions,
+        manager: &WindowManager,
+    ) {
+        let saved = options
+            .persistence_key
+            .as_ref()
+            .and_then(|key| self.context.window_state_store.borrow().get(key));
+        if let Some(title) = options.title {
+            self.set_title(&title).ok_log();
+        }
+        if let Some(style) = options.style {
+            self.set_style(style).ok_log();
+        }
+        if let Some(mut geometry) = options.geometry {
+            let placement = geometry.placement.take();
+            self.set_geometry(geometry).ok_log();
+            if let Some(placement) = placement {
+                let (parent, last) = manager.related_frames(self.window_handle);
+                self.place(&placement, parent, last).ok_log();
+            }
+        }
+        if let Some(saved) = saved {
+            self.restore_state(saved);
+        }
+        self.persistence_key.replace(options.persistence_key);
+        if options.show_when_ready {
+            self.show().ok_log();
+        }
+    }
+
+    fn restore_state(&self, saved: SavedWindowState) {
+        let displays = self.context.display_manager.borrow().get_displays();
+        let frame = constrain_to_work_areas(&saved.frame, &displays, saved.display);
+        self.set_geometry(WindowGeometryRequest {
+            geometry: WindowGeometry {
+                frame_origin: Some(frame.origin()),
+                frame_size: Some(frame.size()),
+                ..Default::default()
+            },
+            preference: GeometryPreference::PreferFrame,
+            placement: None,
+        })
+        .ok_log();
+        // Don't start minimized; The state change is reported synchronously by platform
+        // so it must not happen while window manager is borrowed during window creation
+        if saved.state == WindowState::Maximized || saved.state == WindowState::FullScreen {
+            let weak = self.weak_self.clone_value();
+            self.context
+                .run_loop
+                .borrow()
+                .schedule(
+                    move || {
+                        if let Some(s) = weak.upgrade() {
+                            s.set_state(saved.state).ok_log();
+                        }
+                    },
+                    Duration::ZERO,
+                )
+                .detach();
+        }
+    }
+
+    fn persist_state(&self) {
+        let key = match self.persistence_key.borrow().clone() {
+            Some(key) => key,
+            None => return,
+        };
+        let state = self.get_state().unwrap_or_default();
+        let mut store = self.context.window_state_store.borrow_mut();
+        // Keep the normal frame while maximized so that it can be restored later
+        let frame = match store.get(&key) {
+            Some(saved) if state != WindowState::Normal => Some(saved.frame),
+            // Keep previously saved frame if current one is not known
+            saved => self.frame().or_else(|| saved.map(|s| s.frame)),
+        };
+        if frame.is_none() {
+            warn!("Not saving state of window '{}': frame is not known", key);
+        }
+        if let Some(frame) = frame {
+            let displays = self.context.display_manager.borrow().get_displays();
+            let display = display_for_frame(&frame, &displays).map(|d| d.id);
+            store.set(
+                &key,
+                SavedWindowState {
+                    frame,
+                    display,
+                    state,
+                },
+            );
+        }
+    }
+
+    pub(super) fn set_style(&self, style: WindowStyle) -> Result<()> {
+        self.platform_window()
+            .set_style(style.normalized())
+            .map_err(|e| e.into())
+    }
+
+    pub(super) fn set_state(&self, state: WindowState) -> Result<()> {
         self.platform_window()
-            .set_style(style)
+            .set_state(state)
             .map_err(|e| e.into())
     }
 
+    pub(super) fn get_state(&self) -> Result<WindowState> {
+        self.platform_window().get_state().map_err(|e| e.into())
+    }
+
+    pub(super) fn activate(&self) -> Result<()> {
+        self.platform_window().activate().map_err(|e| e.into())
+    }
+
+    pub(super) fn show_modal<F>(&self, done_callback: F)
+    where
+        F: FnOnce(Result<Value>) + 'static,
+    {
+        self.platform_window()
+            .show_modal(move |result| done_callback(result.map_err(|e| e.into())));
+    }
+
     fn perform_window_drag(&self) -> Result<()> {
         self.platform_window()
             .perform_window_drag()
@@ -216,6 +480,16 @@ impl Window {
         result.map(|v| to_value(v).unwrap()).map_err(|e| e.into())
     }
 
+    fn send_result<T>(reply: WindowMethodCallReply, result: Result<T>)
+    where
+        T: serde::Serialize,
+    {
+        match result {
+            Err(error) if error.is_not_implemented() => reply.send_not_implemented(),
+            result => reply.send(Self::map_result(result)),
+        }
+    }
+
     fn reply<'a, T, F, A>(reply: WindowMethodCallReply, arg: &'a Value, c: F)
     where
         F: FnOnce(A) -> Result<T>,
@@ -225,25 +499,50 @@ impl Window {
         let a: std::result::Result<A, _> = from_value(arg);
         match a {
             Ok(a) => {
-                let res = c(a);
-                let res = Self::map_result(res);
-                reply.send(res);
+                Self::send_result(reply, c(a));
             }
             Err(err) => {
-                reply.send(Self::map_result::<()>(Err(err.into())));
+                reply.send(Err(invalid_request_error(
+                    &format!("Invalid arguments: {}", err),
+                    arg.clone(),
+                )));
             }
         }
     }
 
+    // Methods handled by on_message; Must be kept in sync with the dispatch below
+    pub(super) fn dispatched_methods() -> &'static [&'static str] {
+        &[
+            method::window::SHOW,
+            method::window::SHOW_MODAL,
+            method::window::READY_TO_SHOW,
+            method::window::CLOSE,
+            method::window::CLOSE_WITH_RESULT,
+            method::window::HIDE,
+            method::window::SET_GEOMETRY,
+            method::window::GET_GEOMETRY,
+            method::window::SUPPORTED_GEOMETRY,
+            method::window::SET_STYLE,
+            method::window::SET_TITLE,
+            method::window::SET_STATE,
+            method::window::GET_STATE,
+            method::window::ACTIVATE,
+            method::window::PERFORM_WINDOW_DRAG,
+            method::window::SHOW_POPUP_MENU,
+            method::window::HIDE_POPUP_MENU,
+            method::window::SHOW_SYSTEM_MENU,
+            method::window::SET_WINDOW_MENU,
+            method::drag_source::BEGIN_DRAG_SESSION,
+        ]
+    }
+
     pub(super) fn on_message(&self, method: &str, arg: Value, reply: WindowMethodCallReply) {
         match method {
             method::window::SHOW => {
                 return Self::reply(reply, &arg, |()| self.show());
             }
             method::window::SHOW_MODAL => {
-                return self.platform_window().show_modal(move |result| {
-                    reply.send(Self::map_result(result.map_err(|e| e.into())))
-                });
+                return self.show_modal(move |result| Self::send_result(reply, result));
             }
             method::window::READY_TO_SHOW => {
                 return Self::reply(reply, &arg, |()| self.ready_to_show());
@@ -269,6 +568,18 @@ impl Window {
             method::window::SET_STYLE => {
                 return Self::reply(reply, &arg, |style| self.set_style(style));
             }
+            method::window::SET_TITLE => {
+                return Self::reply(reply, &arg, |title: String| self.set_title(&title));
+            }
+            method::window::SET_STATE => {
+                return Self::reply(reply, &arg, |state| self.set_state(state));
+            }
+            method::window::GET_STATE => {
+                return Self::reply(reply, &arg, |()| self.get_state());
+            }
+            method::window::ACTIVATE => {
+                return Self::reply(reply, &arg, |()| self.activate());
+            }
             method::window::PERFORM_WINDOW_DRAG => {
                 return Self::reply(reply, &arg, |()| self.perform_window_drag());
             }
@@ -277,9 +588,14 @@ impl Window {
                 match request {
                     Ok(request) => {
                         return self
-                            .show_popup_menu(request, move |res| reply.send(Self::map_result(res)))
+                            .show_popup_menu(request, move |res| Self::send_result(reply, res))
+                    }
+                    Err(err) => {
+                        return reply.send(Err(invalid_request_error(
+                            &format!("Invalid arguments: {}", err),
+                            arg,
+                        )))
                     }
-                    Err(err) => return reply.send(Self::map_result::<()>(Err(err.into()))),
                 }
             }
             method::window::HIDE_POPUP_MENU => {
@@ -294,10 +610,16 @@ impl Window {
             method::drag_source::BEGIN_DRAG_SESSION => {
                 return Self::reply(reply, &arg, |request| self.begin_drag_session(request));
             }
-            _ => {}
+            _ => {
+                debug_assert!(
+                    !Self::dispatched_methods().contains(&method),
+                    "{} is not dispatched",
+                    method
+                );
+            }
         }
 
-        reply.send(Ok(Value::Null));
+        reply.send_not_implemented();
     }
 }
 
@@ -305,6 +627,9 @@ pub trait PlatformWindowDelegate {
     fn visibility_changed(&self, visible: bool);
     fn did_request_close(&self);
     fn will_close(&self);
+    fn state_changed(&self, state: WindowState);
+    fn activation_changed(&self, active: bool);
+    fn geometry_changed(&self, geometry: WindowGeometry);
 
     fn dragging_exited(&self);
     fn dragging_updated(&self, info: &DraggingInfo);
@@ -315,6 +640,7 @@ pub trait PlatformWindowDelegate {
 
 impl PlatformWindowDelegate for Window {
     fn visibility_changed(&self, visible: bool) {
+        self.visible.set(visible);
         self.broadcast_message(event::window::VISIBILITY_CHANGED, Value::Bool(visible));
     }
 
@@ -323,10 +649,48 @@ impl PlatformWindowDelegate for Window {
     }
 
     fn will_close(&self) {
+        if self.persistence_key.borrow().is_some() {
+            self.persist_state();
+            self.context.window_state_store.borrow().save();
+        }
         self.broadcast_message(event::window::CLOSE, Value::Null);
         self.context.window_manager.borrow_mut().remove_window(self);
     }
 
+    fn state_changed(&self, state: WindowState) {
+        self.persist_state();
+        self.broadcast_message(event::window::STATE_CHANGED, to_value(state).unwrap());
+    }
+
+    fn activation_changed(&self, active: bool) {
+        self.active.set(active);
+        self.broadcast_message(event::window::ACTIVATION_CHANGED, Value::Bool(active));
+    }
+
+    // Only the last geometry within the interval is delivered; Always delivered from
+    // run loop, as the platform may report geometry changes while window manager
+    // is borrowed (i.e. during window creation)
+    fn geometry_changed(&self, geometry: WindowGeometry) {
+        self.persist_state();
+        let interval = self.geometry_event_interval.get();
+        let previous = self.pending_geometry.replace(Some(geometry));
+        if previous.is_none() {
+            let weak = self.weak_self.clone_value();
+            self.context
+                .run_loop
+                .borrow()
+                .schedule(
+                    move || {
+                        if let Some(s) = weak.upgrade() {
+                            s.flush_geometry_changed();
+                        }
+                    },
+                    interval,
+                )
+                .detach();
+        }
+    }
+
     fn dragging_exited(&self) {
         self.drop_target_invoker()
             .call_method(method::drop_target::DRAGGING_EXITED, Value::Null, |_| {})
@@ -373,3 +737,123 @@ impl PlatformWindowDelegate for Window {
             .ok_log();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, time::Duration};
+
+    use crate::{
+        codec::Value,
+        error_code,
+        shell::{
+            structs::{
+                GeometryPreference, WindowCreateOptions, WindowGeometry, WindowGeometryRequest,
+                WindowState,
+            },
+            Context, Point,
+        },
+    };
+
+    use super::{PlatformWindow, Window};
+
+    fn move_to(x: f64) -> WindowGeometryRequest {
+        WindowGeometryRequest {
+            geometry: WindowGeometry {
+                frame_origin: Some(Point::xy(x, 100.0)),
+                ..Default::default()
+            },
+            preference: GeometryPreference::PreferFrame,
+            placement: None,
+        }
+    }
+
+    #[test]
+    fn test_supported_methods_are_dispatched() {
+        let dispatched = Window::dispatched_methods();
+        for method in PlatformWindow::supported_methods() {
+            assert!(dispatched.contains(method), "{} is not dispatched", method);
+        }
+    }
+
+    #[test]
+    fn test_geometry_changed() {
+        let context = Context::new(Default::default()).unwrap();
+        context
+            .window_manager
+            .borrow_mut()
+            .set_geometry_event_interval(Duration::from_millis(100));
+        // geometry is reported while window manager is borrowed
+        let handle = context
+            .window_manager
+            .borrow_mut()
+            .create_window_with_options(
+                Value::Null,
+                None,
+                WindowCreateOptions {
+                    geometry: Some(move_to(100.0)),
+                    ..Default::default()
+                },
+            );
+        let window = context.window_manager.borrow().get_window(handle).unwrap();
+        let run_loop = context.run_loop.borrow().clone();
+
+        // window is not initialized, so delivered events end up in its queue
+        let queued = window.outbound_queue_len();
+        run_loop.run();
+        assert_eq!(window.outbound_queue_len(), queued + 1);
+
+        // changes within interval collapse into single event with last geometry
+        for x in [200.0, 300.0, 400.0] {
+            window.set_geometry(move_to(x)).unwrap();
+        }
+        let pending = window.pending_geometry.borrow().clone().unwrap();
+        assert_eq!(pending.frame_origin, Some(Point::xy(400.0, 100.0)));
+        assert_eq!(window.outbound_queue_len(), queued + 1);
+        run_loop.run();
+        assert_eq!(window.outbound_queue_len(), queued + 2);
+        assert!(window.pending_geometry.borrow().is_none());
+    }
+
+    #[test]
+    fn test_state() {
+        let context = Context::new(Default::default()).unwrap();
+        let handle = context
+            .window_manager
+            .borrow_mut()
+            .create_window(Value::Null, None);
+        let window = context.window_manager.borrow().get_window(handle).unwrap();
+        assert_eq!(window.get_state().unwrap(), WindowState::Normal);
+        window.set_state(WindowState::Maximized).unwrap();
+        assert_eq!(window.get_state().unwrap(), WindowState::Maximized);
+    }
+
+    #[test]
+    fn test_outbound_queue_overflow() {
+        let context = Context::new(Default::default()).unwrap();
+        let handle = context
+            .window_manager
+            .borrow_mut()
+            .create_window(Value::Null, None);
+        let window = context.window_manager.borrow().get_window(handle).unwrap();
+        let queued = window.outbound_queue_len();
+        window.outbound_queue_limit.set(queued + 1);
+
+        let results = Rc::new(RefCell::new(Vec::new()));
+        for _ in 0..2 {
+            let results = results.clone();
+            window.send_when_initialized(move |res| {
+                results.borrow_mut().push(res.map_err(|e| e.code));
+            });
+        }
+        // message over the limit fails immediately
+        assert_eq!(window.outbound_queue_len(), queued + 1);
+        assert_eq!(
+            *results.borrow(),
+            vec![Err(error_code::OUTBOUND_QUEUE_FULL.to_string())]
+        );
+
+        window.flush_outbound_queue();
+        assert_eq!(results.borrow().len(), 2);
+        assert_eq!(results.borrow()[1], Ok(()));
+    }
+}
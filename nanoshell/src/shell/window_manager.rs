@@ -7,14 +7,19 @@ use crate::{
         value::{from_value, to_value},
         MessageCodec, MessageSender, MethodCallError, StandardMethodCodec, Value,
     },
+    error_code,
     util::OkLog,
+    Error, Result,
 };
 
 use super::{
     constants::*,
     platform::window::{PlatformWindow, PlatformWindowType},
-    Context, EngineHandle, PlatformWindowDelegate, Window, WindowHandle, WindowMethodCall,
-    WindowMethodCallReply,
+    reply_aggregator::ReplyAggregator,
+    structs::WindowCreateOptions,
+    window_method_channel::invalid_request_error,
+    Context, EngineHandle, PlatformWindowDelegate, Rect, Window, WindowHandle, WindowInfo,
+    WindowMethodCall, WindowMethodCallReply, WindowMethodCallResult, WindowProxy,
 };
 
 pub struct WindowManager {
@@ -22,6 +27,17 @@ pub struct WindowManager {
     windows: HashMap<WindowHandle, Rc<Window>>,
     next_handle: WindowHandle,
     engine_to_window: HashMap<EngineHandle, WindowHandle>,
+    outbound_queue_limit: usize,
+    geometry_event_interval: Duration,
+}
+
+const DEFAULT_OUTBOUND_QUEUE_LIMIT: usize = 1024;
+const DEFAULT_GEOMETRY_EVENT_INTERVAL: Duration = Duration::from_micros(16667);
+
+#[derive(serde::Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct WindowInitRequest {
+    protocol_version: Option<i64>,
 }
 
 #[derive(serde::Deserialize)]
@@ -29,6 +45,8 @@ pub struct WindowManager {
 struct WindowCreateRequest {
     parent: WindowHandle,
     init_data: Value,
+    #[serde(default)]
+    options: WindowCreateOptions,
 }
 
 #[derive(serde::Serialize)]
@@ -60,6 +78,26 @@ impl WindowManager {
             windows: HashMap::new(),
             next_handle: WindowHandle(1),
             engine_to_window: HashMap::new(),
+            outbound_queue_limit: DEFAULT_OUTBOUND_QUEUE_LIMIT,
+            geometry_event_interval: DEFAULT_GEOMETRY_EVENT_INTERVAL,
+        }
+    }
+
+    // Maximum number of messages and method calls queued for a window before it is
+    // initialized; Further messages are dropped with a warning.
+    pub fn set_outbound_queue_limit(&mut self, limit: usize) {
+        self.outbound_queue_limit = limit;
+        for window in self.windows.values() {
+            window.outbound_queue_limit.set(limit);
+        }
+    }
+
+    // Minimum interval between geometry-changed events of a window (one frame by
+    // default); With zero interval the latest geometry is delivered on next run loop turn.
+    pub fn set_geometry_event_interval(&mut self, interval: Duration) {
+        self.geometry_event_interval = interval;
+        for window in self.windows.values() {
+            window.geometry_event_interval.set(interval);
         }
     }
 
@@ -67,6 +105,16 @@ impl WindowManager {
         &mut self,
         init_data: Value,
         parent: Option<WindowHandle>,
+    ) -> WindowHandle {
+        self.create_window_with_options(init_data, parent, Default::default())
+    }
+
+    // Options are applied before the window is first shown
+    pub fn create_window_with_options(
+        &mut self,
+        init_data: Value,
+        parent: Option<WindowHandle>,
+        options: WindowCreateOptions,
     ) -> WindowHandle {
         let window_handle = self.next_handle;
         self.next_handle.0 += 1;
@@ -81,6 +129,8 @@ impl WindowManager {
             engine_handle,
             init_data,
             parent,
+            self.outbound_queue_limit,
+            self.geometry_event_interval,
         ));
 
         window.assign_weak_self(Rc::downgrade(&window));
@@ -108,6 +158,7 @@ impl WindowManager {
                 .platform_engine,
         );
         window.platform_window.set(platform_window);
+        window.apply_create_options(options, self);
 
         self.context
             .engine_manager
@@ -118,6 +169,48 @@ impl WindowManager {
         window_handle
     }
 
+    // Frames of parent window and of the most recently created window other than
+    // the given one; Used for window placement
+    pub(super) fn related_frames(&self, handle: WindowHandle) -> (Option<Rect>, Option<Rect>) {
+        let parent = self
+            .windows
+            .get(&handle)
+            .and_then(|w| w.parent)
+            .and_then(|parent| self.windows.get(&parent))
+            .and_then(|w| w.frame());
+        let last = self
+            .windows
+            .iter()
+            .filter(|(h, _)| **h != handle)
+            .max_by_key(|(h, _)| h.0)
+            .and_then(|(_, w)| w.frame());
+        (parent, last)
+    }
+
+    pub fn get_window_proxy(&self, handle: WindowHandle) -> Option<WindowProxy> {
+        self.windows.get(&handle).map(WindowProxy::new)
+    }
+
+    pub fn get_window_proxy_for_engine(&self, engine: EngineHandle) -> Option<WindowProxy> {
+        self.engine_to_window
+            .get(&engine)
+            .and_then(|handle| self.get_window_proxy(*handle))
+    }
+
+    pub fn get_all_window_proxies(&self) -> Vec<WindowProxy> {
+        let mut res: Vec<WindowProxy> = self.windows.values().map(WindowProxy::new).collect();
+        res.sort_by_key(|w| w.handle().0);
+        res
+    }
+
+    // Window that currently has keyboard focus, if it belongs to this application
+    pub fn get_active_window(&self) -> Option<WindowHandle> {
+        self.windows
+            .values()
+            .find(|w| w.active.get())
+            .map(|w| w.window_handle)
+    }
+
     pub fn get_platform_window(&self, handle: WindowHandle) -> Option<PlatformWindowType> {
         self.windows
             .borrow()
@@ -129,11 +222,26 @@ impl WindowManager {
         let engine_handle = window.engine_handle;
         let context_copy = self.context.clone();
 
+        if let Some(window) = self.windows.remove(&window.window_handle) {
+            // Fail messages queued for window that never initialized; Scheduled
+            // because callbacks may need the window manager
+            if !window.initialized.get() {
+                self.context
+                    .run_loop
+                    .borrow()
+                    .schedule(
+                        move || window.discard_outbound_queue(),
+                        Duration::from_secs(0),
+                    )
+                    .detach();
+            }
+        }
+
         // This is a bit hacky; When engine destroy is triggered from flutter
         // platform task runner, we need to schedule this on next run loop turn otherwise
         // it may cause crashes. This particular hack could be avoided by scheduling
-        // every flutter message callback on run loop, but is probably not worth the
-        // overhead.
+        // every flutter message callback on run loop (see
+        // ContextOptions::dispatch_messages_on_run_loop), but that is opt-in.
         self.context
             .run_loop
             .borrow()
@@ -148,20 +256,26 @@ impl WindowManager {
                 Duration::from_secs(0),
             )
             .detach();
-
-        self.windows.remove(&window.window_handle);
     }
 
-    fn on_init(&self, window: WindowHandle) -> Value {
+    fn on_init(&self, window: &Window) -> Value {
         let all_handles = self.windows.keys().map(|h| Value::I64(h.0));
         let all_handles: Vec<Value> = all_handles.collect();
-        let window = self.windows.get(&window).unwrap();
         window.initialized.replace(true);
         let parent = window
             .parent
             .map(|h| h.0.into())
             .unwrap_or_else(|| Value::Null);
+        // only report methods that window actually dispatches to platform window
+        let dispatched = Window::dispatched_methods();
+        let capabilities = PlatformWindow::supported_methods()
+            .iter()
+            .filter(|m| dispatched.contains(m))
+            .map(|m| Value::String((*m).into()))
+            .collect::<Vec<_>>();
         Value::Map(hash_map!(
+            "protocolVersion".into(): protocol::VERSION.into(),
+            "capabilities".into(): capabilities.into(),
             "allWindows".into() : all_handles.into(),
             "currentWindow".into() : window.window_handle.0.into(),
             "initData".into(): window.init_data.clone(),
@@ -169,11 +283,53 @@ impl WindowManager {
         ))
     }
 
-    fn on_create_window(&mut self, argument: Value, parent: WindowHandle) -> Value {
-        let win = self.create_window(argument, Some(parent));
+    fn incompatible_protocol_error(dart_version: Option<i64>) -> MethodCallError<Value> {
+        MethodCallError {
+            code: error_code::INCOMPATIBLE_PROTOCOL.into(),
+            message: Some(format!(
+                "Incompatible nanoshell protocol version (native: {}, dart: {}); \
+                 Make sure nanoshell Dart package and crate versions match",
+                protocol::VERSION,
+                dart_version.map_or_else(|| "unknown".into(), |v| v.to_string()),
+            )),
+            details: Value::Map(hash_map!(
+                "nativeVersion".into(): protocol::VERSION.into(),
+                "dartVersion".into(): dart_version.map(Value::I64).unwrap_or(Value::Null),
+            )),
+        }
+    }
+
+    fn on_create_window(&mut self, request: WindowCreateRequest) -> Value {
+        let win = self.create_window_with_options(
+            request.init_data,
+            Some(request.parent),
+            request.options,
+        );
         to_value(&WindowCreateResponse { window_handle: win }).unwrap()
     }
 
+    pub(super) fn window_infos(&self) -> Vec<WindowInfo> {
+        let mut res: Vec<WindowInfo> = self
+            .windows
+            .values()
+            .map(|w| WindowInfo {
+                handle: w.window_handle,
+                engine: w.engine_handle,
+                parent: w.parent,
+                visible: w.visible.get(),
+                active: w.active.get(),
+                initialized: w.initialized.get(),
+                queued_messages: w.outbound_queue_len(),
+            })
+            .collect();
+        res.sort_by_key(|w| w.handle.0);
+        res
+    }
+
+    pub(super) fn get_window(&self, handle: WindowHandle) -> Option<Rc<Window>> {
+        self.windows.get(&handle).cloned()
+    }
+
     pub(crate) fn message_sender_for_window(
         &self,
         handle: WindowHandle,
@@ -193,34 +349,69 @@ impl WindowManager {
     ) {
         match call.method.as_str() {
             method::window::INIT => {
-                let window = context
-                    .window_manager
-                    .borrow()
-                    .engine_to_window
-                    .get(&engine)
-                    .map(|w| w.clone());
+                let request: WindowInitRequest = match &call.arguments {
+                    Value::Null => Default::default(),
+                    arguments => match from_value(arguments) {
+                        Ok(request) => request,
+                        Err(error) => {
+                            reply.send(Err(invalid_request_error(
+                                &format!("Invalid {} request: {}", call.method, error),
+                                call.arguments,
+                            )));
+                            return;
+                        }
+                    },
+                };
+                let window = {
+                    let window_manager = context.window_manager.borrow();
+                    window_manager
+                        .engine_to_window
+                        .get(&engine)
+                        .and_then(|w| window_manager.get_window(*w))
+                };
+                if request.protocol_version != Some(protocol::VERSION) {
+                    let error = Self::incompatible_protocol_error(request.protocol_version);
+                    if let Some(window) = window {
+                        window.reject_initialization(error.clone());
+                    }
+                    reply.send(Err(error));
+                    return;
+                }
                 match window {
                     Some(window) => {
-                        reply.send(Ok(context.window_manager.borrow().on_init(window)));
+                        reply.send(Ok(context.window_manager.borrow().on_init(&window)));
+                        window.flush_outbound_queue();
                         context
                             .window_method_channel
                             .borrow()
-                            .get_message_broadcaster(window, channel::win::WINDOW_MANAGER)
+                            .get_message_broadcaster(
+                                window.window_handle,
+                                channel::win::WINDOW_MANAGER,
+                            )
                             .broadcast_message(event::window::INITIALIZE, Value::Null);
                     }
                     None => reply.send(Err(MethodCallError {
-                        code: "no-window".into(),
+                        code: error_code::NO_WINDOW.into(),
                         message: Some("No window associated with engine".into()),
                         details: Value::Null,
                     })),
                 }
             }
             method::window::CREATE => {
-                let create_request: WindowCreateRequest = from_value(&call.arguments).unwrap();
+                let create_request: WindowCreateRequest = match from_value(&call.arguments) {
+                    Ok(request) => request,
+                    Err(error) => {
+                        reply.send(Err(invalid_request_error(
+                            &format!("Invalid {} request: {}", call.method, error),
+                            call.arguments,
+                        )));
+                        return;
+                    }
+                };
                 reply.send(Ok(context
                     .window_manager
                     .borrow_mut()
-                    .on_create_window(create_request.init_data, create_request.parent)));
+                    .on_create_window(create_request)));
             }
             _ => {
                 let window = {
@@ -234,32 +425,209 @@ impl WindowManager {
                 if let Some(window) = window {
                     window.on_message(&call.method, call.arguments, reply);
                 } else {
-                    reply.send(Err(MethodCallError {
-                        code: "no-window".into(),
-                        message: Some("Target window not found".into()),
-                        details: Value::Null,
-                    }));
+                    reply.send(Err(
+                        Error::InvalidWindowHandle(call.target_window_handle).into()
+                    ));
                 }
             }
         }
     }
 
+    // Invokes method on given channel in every window. Callback is invoked once all
+    // windows replied; Windows that don't reply within timeout will have Error::Timeout
+    // as result. Calls to windows not yet initialized are queued until initialization.
+    pub fn broadcast_method_call<F>(
+        &self,
+        channel: &str,
+        method: &str,
+        arguments: Value,
+        timeout: Duration,
+        callback: F,
+    ) where
+        F: FnOnce(HashMap<WindowHandle, WindowMethodCallResult>) + 'static,
+    {
+        let windows: Vec<WindowHandle> = self.windows.keys().cloned().collect();
+        let aggregator = ReplyAggregator::new(
+            self.context.clone(),
+            windows.clone(),
+            timeout,
+            |_| Err(Error::Timeout.into()),
+            callback,
+        );
+        let window_method_channel = self.context.window_method_channel.borrow();
+        for window in windows {
+            let invoker = window_method_channel.get_method_invoker(self, window, channel);
+            let res = match invoker {
+                Some(invoker) => {
+                    let aggregator = aggregator.clone();
+                    invoker.call_method(method, arguments.clone(), move |reply| {
+                        aggregator.reply(window, reply)
+                    })
+                }
+                None => Err(Error::InvalidWindowHandle(window)),
+            };
+            if let Err(error) = res {
+                aggregator.reply(window, Err(error.into()));
+            }
+        }
+    }
+
+    // Sends message to engines of all windows and invokes callback with replies
+    // keyed by window once every window replied; Windows that don't reply within
+    // timeout will have Error::Timeout as result. Unlike broadcast_method_call the
+    // message is not queued for windows that are not initialized yet.
+    pub fn broadcast_message_with_replies<F>(
+        &self,
+        channel: &str,
+        message: &[u8],
+        timeout: Duration,
+        callback: F,
+    ) where
+        F: FnOnce(HashMap<WindowHandle, Result<Vec<u8>>>) + 'static,
+    {
+        let aggregator = ReplyAggregator::new(
+            self.context.clone(),
+            self.windows.keys().cloned().collect(),
+            timeout,
+            |_| Err(Error::Timeout),
+            callback,
+        );
+        let engine_manager = self.context.engine_manager.borrow();
+        for window in self.windows.values() {
+            let handle = window.window_handle;
+            let res = match engine_manager.get_engine(window.engine_handle) {
+                Some(engine) => {
+                    let aggregator = aggregator.clone();
+                    engine
+                        .binary_messenger()
+                        .send_message(channel, message, move |reply| {
+                            aggregator.reply(handle, Ok(reply.into()))
+                        })
+                }
+                None => Err(Error::InvalidEngineHandle(window.engine_handle)),
+            };
+            if let Err(error) = res {
+                aggregator.reply(handle, Err(error));
+            }
+        }
+    }
+
     pub(crate) fn broadcast_message(&self, message: Value) {
         let codec: &'static dyn MessageCodec<Value> = &StandardMethodCodec;
         // we use binary messenger directly to be able to encode the message only once
-        let message = codec.encode_message(&message);
+        let message = Rc::new(codec.encode_message(&message));
         for window in self.windows.values() {
-            if !window.initialized.get() {
-                continue;
-            }
-            let manager = self.context.engine_manager.borrow();
-            let engine = manager.get_engine(window.engine_handle);
-            if let Some(engine) = engine {
-                engine
-                    .binary_messenger()
-                    .post_message(channel::DISPATCHER, &message)
-                    .ok_log();
-            }
+            let context = self.context.clone();
+            let engine_handle = window.engine_handle;
+            let message = message.clone();
+            window.send_when_initialized(move |res| {
+                if res.is_err() {
+                    return;
+                }
+                let manager = context.engine_manager.borrow();
+                let engine = manager.get_engine(engine_handle);
+                if let Some(engine) = engine {
+                    engine
+                        .binary_messenger()
+                        .post_message(channel::DISPATCHER, &message)
+                        .ok_log();
+                }
+            });
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::HashMap, rc::Rc, time::Duration};
+
+    use crate::{codec::Value, error_code, shell::Context};
+
+    #[test]
+    fn test_activation() {
+        let context = Context::new(Default::default()).unwrap();
+        let mut window_manager = context.window_manager.borrow_mut();
+        let first = window_manager.create_window(Value::Null, None);
+        let second = window_manager.create_window(Value::Null, None);
+        assert_eq!(window_manager.get_active_window(), None);
+
+        let first_proxy = window_manager.get_window_proxy(first).unwrap();
+        let second_proxy = window_manager.get_window_proxy(second).unwrap();
+        drop(window_manager);
+
+        first_proxy.activate().unwrap();
+        assert_eq!(
+            context.window_manager.borrow().get_active_window(),
+            Some(first)
+        );
+
+        second_proxy.activate().unwrap();
+        assert_eq!(
+            context.window_manager.borrow().get_active_window(),
+            Some(second)
+        );
+        assert!(!first_proxy.is_active());
+    }
+
+    #[test]
+    fn test_remove_uninitialized_window() {
+        let context = Context::new(Default::default()).unwrap();
+        let handle = context
+            .window_manager
+            .borrow_mut()
+            .create_window(Value::Null, None);
+        let window = context.window_manager.borrow().get_window(handle).unwrap();
+
+        let result = Rc::new(RefCell::new(None));
+        let result_copy = result.clone();
+        window.send_when_initialized(move |res| {
+            result_copy.replace(Some(res));
+        });
+        context.window_manager.borrow_mut().remove_window(&window);
+        let run_loop = context.run_loop.borrow().clone();
+        run_loop.run();
+
+        assert_eq!(window.outbound_queue_len(), 0);
+        let error = result.borrow_mut().take().unwrap().err().unwrap();
+        assert_eq!(error.code, error_code::INVALID_WINDOW_HANDLE);
+    }
+
+    #[test]
+    fn test_broadcast_message_with_replies() {
+        let context = Context::new(Default::default()).unwrap();
+        let mut window_manager = context.window_manager.borrow_mut();
+        let first = window_manager.create_window(Value::Null, None);
+        let second = window_manager.create_window(Value::Null, None);
+        drop(window_manager);
+
+        let replies = Rc::new(RefCell::new(None));
+        let replies_copy = replies.clone();
+        context
+            .window_manager
+            .borrow()
+            .broadcast_message_with_replies(
+                "channel",
+                &[1, 2, 3],
+                Duration::from_secs(1),
+                move |r| {
+                    replies_copy.replace(Some(r));
+                },
+            );
+        // callback is never invoked synchronously
+        assert!(replies.borrow().is_none());
+        let run_loop = context.run_loop.borrow().clone();
+        run_loop.run();
+
+        // null platform engines can't send messages
+        let replies: HashMap<_, _> = replies
+            .borrow_mut()
+            .take()
+            .unwrap()
+            .into_iter()
+            .map(|(window, reply)| (window, reply.map_err(|e| e.code())))
+            .collect();
+        assert_eq!(replies.len(), 2);
+        assert_eq!(replies[&first], Err(error_code::PLATFORM_NOT_IMPLEMENTED));
+        assert_eq!(replies[&second], Err(error_code::PLATFORM_NOT_IMPLEMENTED));
+    }
+}
@@ -1,13 +1,21 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::{Rc, Weak},
+};
 
 use velcro::hash_map;
 
 use crate::{
-    codec::{MessageReply, MessageSender, MethodCallError, Value},
-    Result,
+    codec::{MessageReply, MessageSender, MethodCallError, StandardMethodCodec, Value},
+    error_code,
+    util::{trace, OkLog},
+    Error, Result,
 };
 
-use super::{constants::channel, Context, EngineHandle, WindowHandle, WindowManager};
+use super::{
+    constants::channel, CallOptions, Context, EngineHandle, Window, WindowHandle, WindowManager,
+};
 
 pub struct WindowMethodChannel {
     context: Rc<Context>,
@@ -31,13 +39,20 @@ impl WindowMethodCallReply {
     pub fn send(self, result: WindowMethodCallResult) {
         self.reply.send(encode_result(result));
     }
+
+    // Null reply; Reported in Dart as MissingPluginException
+    pub fn send_not_implemented(self) {
+        self.reply.send(Value::Null);
+    }
 }
 
 #[derive(Clone)]
 pub struct WindowMethodInvoker {
+    context: Rc<Context>,
     sender: MessageSender<Value>,
     channel_name: String,
     target_window_handle: WindowHandle,
+    target_window: Weak<Window>,
 }
 
 #[derive(Clone)]
@@ -67,14 +82,116 @@ impl WindowMethodInvoker {
     where
         F: FnOnce(WindowMethodCallResult) -> () + 'static,
     {
-        self.sender.send_message(
+        self.call_method_with_options(method, arguments, Default::default(), reply)
+    }
+
+    // Like "call_method", but the call can time out (resolving with MethodCallError)
+    // or be cancelled (reply is dropped without being called). If target window is
+    // not initialized yet, the call is queued; Time spent in queue counts towards
+    // the timeout.
+    pub fn call_method_with_options<F>(
+        &self,
+        method: &str,
+        arguments: Value,
+        options: CallOptions,
+        reply: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(WindowMethodCallResult) + 'static,
+    {
+        let window = self.target_window.upgrade();
+        match window {
+            Some(window) if !window.initialized.get() => {
+                if matches!(&options.cancellation_token, Some(token) if token.is_cancelled()) {
+                    return Ok(());
+                }
+                let reply = Rc::new(RefCell::new(Some(reply)));
+                let timeout = options.timeout.map(|timeout| {
+                    let reply = reply.clone();
+                    let context = self.context.clone();
+                    self.context.run_loop.borrow().schedule(
+                        move || {
+                            let reply = reply.borrow_mut().take();
+                            if let Some(reply) = reply {
+                                context.channel_metrics.record_error(channel::DISPATCHER);
+                                reply(Err(Error::Timeout.into()));
+                            }
+                        },
+                        timeout,
+                    )
+                });
+                // drops the reply if call is cancelled while queued; Once sent the
+                // call registers with the token again
+                let cancellation = options.cancellation_token.as_ref().map(|token| {
+                    let reply = reply.clone();
+                    token.register(move || {
+                        let reply = reply.borrow_mut().take();
+                        drop(reply);
+                    })
+                });
+                let invoker = self.clone();
+                let method: String = method.into();
+                // timeout is already running
+                let options = CallOptions {
+                    timeout: None,
+                    ..options
+                };
+                window.send_when_initialized(move |res| {
+                    drop(cancellation);
+                    if let Err(error) = res {
+                        let reply = reply.borrow_mut().take();
+                        if let Some(reply) = reply {
+                            reply(Err(error));
+                        }
+                        return;
+                    }
+                    if reply.borrow().is_none() {
+                        // timed out or cancelled while queued
+                        return;
+                    }
+                    let reply_copy = reply.clone();
+                    let res = invoker.send_method_call(&method, arguments, options, move |r| {
+                        drop(timeout);
+                        let reply = reply_copy.borrow_mut().take();
+                        if let Some(reply) = reply {
+                            reply(r);
+                        }
+                    });
+                    if let Err(error) = res {
+                        let reply = reply.borrow_mut().take();
+                        if let Some(reply) = reply {
+                            reply(Err(error.into()));
+                        }
+                    }
+                });
+                Ok(())
+            }
+            _ => self.send_method_call(method, arguments, options, reply),
+        }
+    }
+
+    fn send_method_call<F>(
+        &self,
+        method: &str,
+        arguments: Value,
+        options: CallOptions,
+        reply: F,
+    ) -> Result<()>
+    where
+        F: FnOnce(WindowMethodCallResult) + 'static,
+    {
+        self.sender.send_message_with_options(
             &encode_method_call(WindowMethodCall {
                 target_window_handle: self.target_window_handle,
                 method: method.into(),
                 channel: self.channel_name.clone(),
                 arguments,
             }),
-            move |value| reply(decode_result(value)),
+            options,
+            move |value| match value {
+                Ok(value) => reply(decode_result(value)),
+                Err(error) => reply(Err(error.into())),
+            },
         )
     }
 }
@@ -91,7 +208,8 @@ impl WindowMethodChannel {
             .message_manager
             .borrow_mut()
             .register_message_handler(
-                channel::DISPATCHER, //
+                channel::DISPATCHER,
+                &StandardMethodCodec,
                 move |message, reply, engine| {
                     Self::on_message(
                         context_copy.clone(),
@@ -119,18 +237,27 @@ impl WindowMethodChannel {
         self.handlers.as_ref().borrow_mut().remove(channel);
     }
 
+    pub(super) fn registered_channels(&self) -> Vec<String> {
+        let mut res: Vec<String> = self.handlers.borrow().keys().cloned().collect();
+        res.sort();
+        res
+    }
+
     pub fn get_method_invoker(
         &self,
         window_manager: &WindowManager,
         window: WindowHandle,
         channel_name: &str,
     ) -> Option<WindowMethodInvoker> {
+        let target_window = window_manager.get_window(window)?;
         window_manager
             .message_sender_for_window(window, channel::DISPATCHER)
             .map(|sender| WindowMethodInvoker {
+                context: self.context.clone(),
                 sender: sender,
                 channel_name: channel_name.into(),
                 target_window_handle: window,
+                target_window: Rc::downgrade(&target_window),
             })
     }
 
@@ -153,7 +280,17 @@ impl WindowMethodChannel {
         reply: MessageReply<Value>,
         engine: EngineHandle,
     ) {
-        let call = decode_method_call(message);
+        let call = match decode_method_call(message) {
+            Ok(call) => call,
+            Err(error) => {
+                context.channel_metrics.record_error(channel::DISPATCHER);
+                reply.send(encode_result(Err(error)));
+                return;
+            }
+        };
+        let span =
+            trace::window_method_span(&call.channel, &call.method, call.target_window_handle.0);
+        let _guard = span.enter();
         let handlers = handlers.as_ref().borrow();
         let handler = handlers.get(&call.channel);
         match handler {
@@ -161,16 +298,38 @@ impl WindowMethodChannel {
             Some(handler) => {
                 handler(call, WindowMethodCallReply { reply }, engine);
             }
-            // no handler, forward message to target window
+            // no handler, forward message to target window; Queued until the
+            // target window is initialized
             None => {
-                let sender = context
-                    .window_manager
-                    .borrow()
-                    .message_sender_for_window(call.target_window_handle, channel::DISPATCHER);
-                if let Some(sender) = sender {
-                    sender
-                        .send_message(&encode_method_call(call), |reply_in| reply.send(reply_in))
-                        .ok();
+                let (window, sender) = {
+                    let window_manager = context.window_manager.borrow();
+                    (
+                        window_manager.get_window(call.target_window_handle),
+                        window_manager.message_sender_for_window(
+                            call.target_window_handle,
+                            channel::DISPATCHER,
+                        ),
+                    )
+                };
+                match (window, sender) {
+                    (Some(window), Some(sender)) => {
+                        let message = encode_method_call(call);
+                        window.send_when_initialized(move |res| match res {
+                            Ok(()) => {
+                                sender
+                                    .send_message(&message, |reply_in| match reply_in {
+                                        Ok(reply_in) => reply.send(reply_in),
+                                        Err(error) => reply.send(encode_result(Err(error.into()))),
+                                    })
+                                    .ok_log();
+                            }
+                            Err(error) => reply.send(encode_result(Err(error))),
+                        });
+                    }
+                    _ => reply.send(encode_result(Err(Error::InvalidWindowHandle(
+                        call.target_window_handle,
+                    )
+                    .into()))),
                 }
             }
         }
@@ -204,7 +363,17 @@ fn encode_message(handle: &WindowHandle, channel: &str, message: &str, arguments
     })
 }
 
-fn decode_method_call(call: Value) -> WindowMethodCall {
+pub(super) fn invalid_request_error(message: &str, details: Value) -> MethodCallError<Value> {
+    MethodCallError {
+        code: error_code::INVALID_REQUEST.into(),
+        message: Some(message.into()),
+        details,
+    }
+}
+
+fn decode_method_call(
+    call: Value,
+) -> std::result::Result<WindowMethodCall, MethodCallError<Value>> {
     if let Value::Map(mut map) = call {
         let target_window_handle = map.remove(&"targetWindowHandle".into());
         let method = map.remove(&"method".into());
@@ -216,33 +385,52 @@ fn decode_method_call(call: Value) -> WindowMethodCall {
                 Some(Value::I64(target_window_handle)),
                 Some(Value::String(method)),
                 Some(Value::String(channel)),
-            ) => WindowMethodCall {
+            ) => Ok(WindowMethodCall {
                 target_window_handle: WindowHandle(target_window_handle),
                 method: method,
                 channel: channel,
                 arguments: arguments.unwrap_or_else(|| Value::Null),
-            },
-            _ => {
-                panic!("Invalid method call")
-            }
+            }),
+            (target_window_handle, method, channel) => Err(invalid_request_error(
+                "Method call must have targetWindowHandle, method and channel",
+                Value::Map(hash_map! {
+                    "targetWindowHandle".into() : target_window_handle.unwrap_or(Value::Null),
+                    "method".into() : method.unwrap_or(Value::Null),
+                    "channel".into() : channel.unwrap_or(Value::Null),
+                }),
+            )),
         }
     } else {
-        panic!("Invalid method call");
+        Err(invalid_request_error("Method call must be a map", call))
     }
 }
 
 fn decode_result(result: Value) -> WindowMethodCallResult {
+    let invalid_reply = |details| {
+        Err(MethodCallError {
+            code: error_code::INVALID_REPLY.into(),
+            message: Some(
+                "Reply must contain either result or error code and optional message".into(),
+            ),
+            details,
+        })
+    };
+    if let Value::Null = result {
+        return Err(MethodCallError::not_implemented());
+    }
     if let Value::Map(mut map) = result {
         let code = map.remove(&"code".into());
         let message = map.remove(&"message".into());
         let details = map.remove(&"details".into());
         let result = map.remove(&"result".into());
         match (code, message, details, result) {
-            (Some(Value::String(code)), None, details, None) => Err(MethodCallError {
-                code: code,
-                message: None,
-                details: details.unwrap_or(Value::Null),
-            }),
+            (Some(Value::String(code)), None | Some(Value::Null), details, None) => {
+                Err(MethodCallError {
+                    code: code,
+                    message: None,
+                    details: details.unwrap_or(Value::Null),
+                })
+            }
             (Some(Value::String(code)), Some(Value::String(message)), details, None) => {
                 Err(MethodCallError {
                     code: code,
@@ -251,30 +439,232 @@ fn decode_result(result: Value) -> WindowMethodCallResult {
                 })
             }
             (None, None, None, Some(value)) => Ok(value),
-            (_, _, _, _) => panic!("Invalid value"),
+            (code, message, details, result) => invalid_reply(Value::Map(hash_map! {
+                "code".into() : code.unwrap_or(Value::Null),
+                "message".into() : message.unwrap_or(Value::Null),
+                "details".into() : details.unwrap_or(Value::Null),
+                "result".into() : result.unwrap_or(Value::Null),
+            })),
         }
     } else {
-        panic!("Invalid value");
+        invalid_reply(result)
     }
 }
 
-fn encode_error(code: &str, message: Option<&str>, details: Value) -> Value {
-    let message = match message {
-        Some(message) => message.into(),
-        None => Value::Null,
-    };
-    Value::Map(hash_map! {
-        "code".into() : code.into(),
-        "message".into() : message,
-        "details".into() : details,
-    })
-}
-
 fn encode_result(result: WindowMethodCallResult) -> Value {
     match result {
         Ok(value) => Value::Map(hash_map! {
             "result".into() : value,
         }),
-        Err(error) => encode_error(&error.code, error.message.as_deref(), error.details),
+        Err(error) => error.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, time::Duration};
+
+    use velcro::hash_map;
+
+    use crate::{
+        codec::{MessageCodec, MethodCallError, StandardMethodCodec, Value},
+        shell::{CallOptions, CancellationToken, Context, WindowHandle},
+    };
+
+    use super::{
+        decode_method_call, decode_result, error_code, WindowMethodCallResult, WindowMethodInvoker,
+    };
+
+    fn garbage() -> Vec<Value> {
+        vec![
+            Value::Bool(true),
+            Value::I64(10),
+            Value::String("method:window:show".into()),
+            Value::List(vec![Value::Null, Value::I64(1)]),
+            Value::Map(hash_map! {}),
+            Value::Map(hash_map! {
+                "targetWindowHandle".into() : "1".into(),
+                "method".into() : "method:window:show".into(),
+                "channel".into() : ".window.window-manager".into(),
+            }),
+            Value::Map(hash_map! {
+                "targetWindowHandle".into() : 1i64.into(),
+                "method".into() : Value::Null,
+            }),
+            Value::Map(hash_map! {
+                "code".into() : 10i64.into(),
+            }),
+            Value::Map(hash_map! {
+                "code".into() : "error".into(),
+                "result".into() : Value::Null,
+            }),
+            Value::Map(hash_map! {
+                "code".into() : "error".into(),
+                "message".into() : 5i64.into(),
+            }),
+        ]
+    }
+
+    #[test]
+    fn test_decode_method_call() {
+        for value in garbage().into_iter().chain(vec![Value::Null]) {
+            let error = decode_method_call(value).err().unwrap();
+            assert_eq!(error.code, error_code::INVALID_REQUEST);
+        }
+
+        let call = decode_method_call(Value::Map(hash_map! {
+            "targetWindowHandle".into() : 1i64.into(),
+            "method".into() : "method:window:show".into(),
+            "channel".into() : ".window.window-manager".into(),
+        }))
+        .ok()
+        .unwrap();
+        assert_eq!(call.target_window_handle.0, 1);
+        assert_eq!(call.method, "method:window:show");
+        assert!(matches!(call.arguments, Value::Null));
+    }
+
+    #[test]
+    fn test_decode_result() {
+        for value in garbage() {
+            let error = decode_result(value).err().unwrap();
+            assert_eq!(error.code, error_code::INVALID_REPLY);
+        }
+
+        let error = decode_result(Value::Null).err().unwrap();
+        assert_eq!(error.code, error_code::NOT_IMPLEMENTED);
+
+        let result = decode_result(Value::Map(hash_map! {
+            "result".into() : 10i64.into(),
+        }));
+        assert!(matches!(result, Ok(Value::I64(10))));
+
+        let error = decode_result(Value::Map(hash_map! {
+            "code".into() : "some-error".into(),
+            "message".into() : Value::Null,
+            "details".into() : Value::Null,
+        }))
+        .err()
+        .unwrap();
+        assert_eq!(error.code, "some-error");
+        assert_eq!(error.message, None);
+    }
+
+    #[test]
+    fn test_decode_garbage_bytes() {
+        let codec: &dyn MessageCodec<Value> = &StandardMethodCodec;
+        let mut seed = 17u32;
+        for len in 0..512 {
+            let bytes: Vec<u8> = (0..len)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                    (seed >> 16) as u8
+                })
+                .collect();
+            if let Some(value) = codec.decode_message(&bytes) {
+                // must not panic regardless of decoded shape
+                let _ = decode_method_call(value.clone());
+                let _ = decode_result(value);
+            }
+        }
+    }
+
+    fn create_window(context: &Context) -> (WindowHandle, WindowMethodInvoker) {
+        let window = context
+            .window_manager
+            .borrow_mut()
+            .create_window_with_options(Value::Null, None, Default::default());
+        let invoker = context
+            .window_method_channel
+            .borrow()
+            .get_method_invoker(&context.window_manager.borrow(), window, "channel")
+            .unwrap();
+        (window, invoker)
+    }
+
+    fn call_method(
+        invoker: &WindowMethodInvoker,
+        options: CallOptions,
+    ) -> Rc<RefCell<Option<WindowMethodCallResult>>> {
+        let result = Rc::new(RefCell::new(None));
+        let result_copy = result.clone();
+        invoker
+            .call_method_with_options("method", Value::Null, options, move |r| {
+                result_copy.replace(Some(r));
+            })
+            .unwrap();
+        result
+    }
+
+    #[test]
+    fn test_queued_call_timeout() {
+        let context = Context::new(Default::default()).unwrap();
+        let (_, invoker) = create_window(&context);
+
+        // window never initializes; call must time out while still queued
+        let result = call_method(&invoker, CallOptions::with_timeout(Duration::from_secs(1)));
+        let run_loop = context.run_loop.borrow().clone();
+        run_loop.run();
+
+        let error = result.borrow_mut().take().unwrap().err().unwrap();
+        assert_eq!(error.code, error_code::TIMEOUT);
+    }
+
+    #[test]
+    fn test_queued_call_rejected() {
+        let context = Context::new(Default::default()).unwrap();
+        let (window, invoker) = create_window(&context);
+
+        let queued = call_method(&invoker, Default::default());
+        assert!(queued.borrow().is_none());
+
+        let window = context.window_manager.borrow().get_window(window).unwrap();
+        window.reject_initialization(MethodCallError {
+            code: error_code::INCOMPATIBLE_PROTOCOL.into(),
+            message: None,
+            details: Value::Null,
+        });
+        assert_eq!(window.outbound_queue_len(), 0);
+        let error = queued.borrow_mut().take().unwrap().err().unwrap();
+        assert_eq!(error.code, error_code::INCOMPATIBLE_PROTOCOL);
+
+        // calls made after rejection fail immediately
+        let result = call_method(&invoker, Default::default());
+        let error = result.borrow_mut().take().unwrap().err().unwrap();
+        assert_eq!(error.code, error_code::INCOMPATIBLE_PROTOCOL);
+    }
+
+    #[test]
+    fn test_queued_call_cancelled() {
+        let context = Context::new(Default::default()).unwrap();
+        let (window, invoker) = create_window(&context);
+
+        let token = CancellationToken::new();
+        let result = call_method(
+            &invoker,
+            CallOptions {
+                timeout: Some(Duration::from_secs(1)),
+                cancellation_token: Some(token.clone()),
+            },
+        );
+        token.cancel();
+
+        // neither the timeout nor the queue flush may invoke cancelled reply
+        let run_loop = context.run_loop.borrow().clone();
+        run_loop.run();
+        let window = context.window_manager.borrow().get_window(window).unwrap();
+        window.flush_outbound_queue();
+        assert!(result.borrow().is_none());
+
+        // already cancelled token - call is not queued at all
+        let queued = window.outbound_queue_len();
+        call_method(
+            &invoker,
+            CallOptions {
+                timeout: None,
+                cancellation_token: Some(token),
+            },
+        );
+        assert_eq!(window.outbound_queue_len(), queued);
     }
 }
@@ -0,0 +1,358 @@
+use super::{
+    structs::{Display, WindowPlacement, WindowPlacementPolicy},
+    Point, Rect, Size,
+};
+
+// Offset of cascaded window from the previous one
+const CASCADE_OFFSET: f64 = 24.0;
+
+fn overlap(a: &Rect, b: &Rect) -> f64 {
+    let width = a.x2().min(b.x2()) - a.x.max(b.x);
+    let height = a.y2().min(b.y2()) - a.y.max(b.y);
+    if width > 0.0 && height > 0.0 {
+        width * height
+    } else {
+        0.0
+    }
+}
+
+// Display whose work area overlaps the frame most
+pub fn display_for_frame<'a>(frame: &Rect, displays: &'a [Display]) -> Option<&'a Display> {
+    displays
+        .iter()
+        .map(|d| (d, overlap(frame, &d.work_area)))
+        .filter(|(_, overlap)| *overlap > 0.0)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(d, _)| d)
+}
+
+// Moves (and shrinks if necessary) the frame to fit the work area of display
+// it overlaps most; Frames that are entirely off-screen are moved to preferred
+// display, or the first one if preferred display is no longer present.
+pub fn constrain_to_work_areas(frame: &Rect, displays: &[Display], preferred: Option<i64>) -> Rect {
+    let display = display_for_frame(frame, displays)
+        .or_else(|| displays.iter().find(|d| Some(d.id) == preferred))
+        .or_else(|| displays.first());
+    match display {
+        Some(display) => {
+            let area = &display.work_area;
+            let width = frame.width.min(area.width);
+            let height = frame.height.min(area.height);
+            Rect::xywh(
+                frame.x.max(area.x).min(area.x2() - width),
+                frame.y.max(area.y).min(area.y2() - height),
+                width,
+                height,
+            )
+        }
+        None => frame.clone(),
+    }
+}
+
+// Returns the frame for window with given frame after applying placement; Parent
+// and last window frames are used by CenterOnParent and Cascade policies.
+pub fn place_frame(
+    frame: &Rect,
+    placement: &WindowPlacement,
+    displays: &[Display],
+    parent: Option<&Rect>,
+    last: Option<&Rect>,
+) -> Rect {
+    // Display of parent window (or the window itself) or primary display
+    let display = parent
+        .and_then(|parent| display_for_frame(parent, displays))
+        .or_else(|| display_for_frame(frame, displays))
+        .or_else(|| displays.first());
+    let center_on_display = || match display {
+        Some(display) => centered(&frame.size(), &display.work_area),
+        None => frame.clone(),
+    };
+    let res = match placement.policy {
+        Some(WindowPlacementPolicy::CenterOnParent) => match parent {
+            Some(parent) => centered(&frame.size(), parent),
+            None => center_on_display(),
+        },
+        Some(WindowPlacementPolicy::CenterOnDisplay) => center_on_display(),
+        Some(WindowPlacementPolicy::Cascade) => match last {
+            Some(last) => cascaded(&frame.size(), last, displays),
+            None => center_on_display(),
+        },
+        Some(WindowPlacementPolicy::Anchor) => match &placement.anchor {
+            Some(anchor) => anchored(&frame.size(), anchor, displays),
+            None => frame.clone(),
+        },
+        None => frame.clone(),
+    };
+    if placement.constrain_to_work_area {
+        constrain_to_work_areas(&res, displays, None)
+    } else {
+        res
+    }
+}
+
+fn centered(size: &Size, rect: &Rect) -> Rect {
+    let center = rect.center();
+    Rect::xywh(
+        center.x - size.width / 2.0,
+        center.y - size.height / 2.0,
+        size.width,
+        size.height,
+    )
+}
+
+// Offset from last frame; Starts again from top left corner of work area when
+// the window would not fit
+fn cascaded(size: &Size, last: &Rect, displays: &[Display]) -> Rect {
+    let res = Rect::origin_size(
+        &last
+            .origin()
+            .translated(&Point::xy(CASCADE_OFFSET, CASCADE_OFFSET)),
+        size,
+    );
+    match display_for_frame(last, displays) {
+        Some(display) => {
+            let area = &display.work_area;
+            if res.x2() > area.x2() || res.y2() > area.y2() {
+                Rect::origin_size(&area.origin(), size)
+            } else {
+                res
+            }
+        }
+        None => res,
+    }
+}
+
+// Left aligned below the anchor; Above the anchor if there is not enough space
+// below it, and shifted left if it would extend past right edge of work area
+fn anchored(size: &Size, anchor: &Rect, displays: &[Display]) -> Rect {
+    let mut res = Rect::xywh(anchor.x, anchor.y2(), size.width, size.height);
+    if let Some(display) = display_for_frame(anchor, displays) {
+        let area = &display.work_area;
+        if res.y2() > area.y2() && anchor.y - size.height >= area.y {
+            res.y = anchor.y - size.height;
+        }
+        if res.x2() > area.x2() {
+            res.x = (area.x2() - size.width).max(area.x);
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        codec::Value,
+        shell::{
+            structs::{
+                Display, GeometryPreference, WindowCreateOptions, WindowGeometry,
+                WindowGeometryRequest, WindowPlacement, WindowPlacementPolicy,
+            },
+            Context, Rect, Size,
+        },
+    };
+
+    use super::{constrain_to_work_areas, place_frame};
+
+    fn displays() -> Vec<Display> {
+        vec![
+            Display {
+                id: 1,
+                frame: Rect::xywh(0.0, 0.0, 1920.0, 1080.0),
+                work_area: Rect::xywh(0.0, 25.0, 1920.0, 1055.0),
+                scale: 2.0,
+            },
+            Display {
+                id: 2,
+                frame: Rect::xywh(1920.0, 0.0, 1280.0, 1024.0),
+                work_area: Rect::xywh(1920.0, 0.0, 1280.0, 1024.0),
+                scale: 1.0,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_constrain() {
+        let displays = displays();
+
+        // fully visible
+        let frame = Rect::xywh(100.0, 100.0, 800.0, 600.0);
+        assert_eq!(constrain_to_work_areas(&frame, &displays, None), frame);
+
+        // partially off-screen on second display
+        assert_eq!(
+            constrain_to_work_areas(&Rect::xywh(3000.0, 900.0, 800.0, 600.0), &displays, None),
+            Rect::xywh(2400.0, 424.0, 800.0, 600.0)
+        );
+
+        // display no longer present
+        assert_eq!(
+            constrain_to_work_areas(&Rect::xywh(-2000.0, 0.0, 800.0, 600.0), &displays, Some(3)),
+            Rect::xywh(0.0, 25.0, 800.0, 600.0)
+        );
+
+        // off-screen, preferred display present; too large
+        assert_eq!(
+            constrain_to_work_areas(&Rect::xywh(5000.0, 0.0, 1600.0, 1200.0), &displays, Some(2)),
+            Rect::xywh(1920.0, 0.0, 1280.0, 1024.0)
+        );
+
+        // invalid origin does not panic and ends up in work area
+        assert_eq!(
+            constrain_to_work_areas(&Rect::xywh(f64::NAN, 0.0, 800.0, 600.0), &displays, None),
+            Rect::xywh(0.0, 25.0, 800.0, 600.0)
+        );
+    }
+
+    fn placement(policy: WindowPlacementPolicy) -> WindowPlacement {
+        WindowPlacement {
+            policy: Some(policy),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_place() {
+        let displays = displays();
+        let frame = Rect::xywh(0.0, 0.0, 400.0, 300.0);
+        let parent = Rect::xywh(2000.0, 100.0, 800.0, 600.0);
+
+        let center = placement(WindowPlacementPolicy::CenterOnParent);
+        assert_eq!(
+            place_frame(&frame, &center, &displays, Some(&parent), None),
+            Rect::xywh(2200.0, 250.0, 400.0, 300.0)
+        );
+        // no parent - centered on display
+        assert_eq!(
+            place_frame(&frame, &center, &displays, None, None),
+            Rect::xywh(760.0, 402.5, 400.0, 300.0)
+        );
+
+        // centered on work area of parent display
+        assert_eq!(
+            place_frame(
+                &frame,
+                &placement(WindowPlacementPolicy::CenterOnDisplay),
+                &displays,
+                Some(&parent),
+                None
+            ),
+            Rect::xywh(2360.0, 362.0, 400.0, 300.0)
+        );
+
+        let cascade = placement(WindowPlacementPolicy::Cascade);
+        assert_eq!(
+            place_frame(&frame, &cascade, &displays, None, Some(&parent)),
+            Rect::xywh(2024.0, 124.0, 400.0, 300.0)
+        );
+        // would not fit, start from top left corner of work area
+        assert_eq!(
+            place_frame(
+                &frame,
+                &cascade,
+                &displays,
+                None,
+                Some(&Rect::xywh(1500.0, 700.0, 400.0, 300.0))
+            ),
+            Rect::xywh(0.0, 25.0, 400.0, 300.0)
+        );
+
+        let anchor = |rect: Rect| WindowPlacement {
+            policy: Some(WindowPlacementPolicy::Anchor),
+            anchor: Some(rect),
+            ..Default::default()
+        };
+        assert_eq!(
+            place_frame(
+                &frame,
+                &anchor(Rect::xywh(100.0, 100.0, 50.0, 20.0)),
+                &displays,
+                None,
+                None
+            ),
+            Rect::xywh(100.0, 120.0, 400.0, 300.0)
+        );
+        // not enough space below and on the right
+        assert_eq!(
+            place_frame(
+                &frame,
+                &anchor(Rect::xywh(1700.0, 900.0, 50.0, 20.0)),
+                &displays,
+                None,
+                None
+            ),
+            Rect::xywh(1520.0, 600.0, 400.0, 300.0)
+        );
+
+        // constrain only
+        assert_eq!(
+            place_frame(
+                &Rect::xywh(-100.0, 0.0, 400.0, 300.0),
+                &WindowPlacement {
+                    constrain_to_work_area: true,
+                    ..Default::default()
+                },
+                &displays,
+                None,
+                None
+            ),
+            Rect::xywh(0.0, 25.0, 400.0, 300.0)
+        );
+    }
+
+    #[test]
+    fn test_window_placement() {
+        let context = Context::new(Default::default()).unwrap();
+        let frame_request = |frame: Rect, placement| WindowGeometryRequest {
+            geometry: WindowGeometry {
+                frame_origin: Some(frame.origin()),
+                frame_size: Some(frame.size()),
+                ..Default::default()
+            },
+            preference: GeometryPreference::PreferFrame,
+            placement,
+        };
+        let mut window_manager = context.window_manager.borrow_mut();
+        let parent = window_manager.create_window_with_options(
+            Value::Null,
+            None,
+            WindowCreateOptions {
+                geometry: Some(frame_request(Rect::xywh(100.0, 100.0, 800.0, 600.0), None)),
+                ..Default::default()
+            },
+        );
+        let child = window_manager.create_window_with_options(
+            Value::Null,
+            Some(parent),
+            WindowCreateOptions {
+                geometry: Some(WindowGeometryRequest {
+                    geometry: WindowGeometry {
+                        frame_size: Some(Size::wh(400.0, 200.0)),
+                        ..Default::default()
+                    },
+                    preference: GeometryPreference::PreferFrame,
+                    placement: Some(placement(WindowPlacementPolicy::CenterOnParent)),
+                }),
+                ..Default::default()
+            },
+        );
+        let parent_window = window_manager.get_window(parent).unwrap();
+        let child_window = window_manager.get_window(child).unwrap();
+        drop(window_manager);
+        assert_eq!(
+            child_window.frame().unwrap(),
+            Rect::xywh(300.0, 300.0, 400.0, 200.0)
+        );
+
+        // cascade from the child, which was created last
+        parent_window
+            .set_geometry(frame_request(
+                Rect::xywh(100.0, 100.0, 800.0, 600.0),
+                Some(placement(WindowPlacementPolicy::Cascade)),
+            ))
+            .unwrap();
+        assert_eq!(
+            parent_window.frame().unwrap(),
+            Rect::xywh(324.0, 324.0, 800.0, 600.0)
+        );
+    }
+}
@@ -0,0 +1,113 @@
+use std::rc::{Rc, Weak};
+
+use crate::{codec::Value, Error, Result};
+
+use super::{
+    structs::{
+        WindowGeometry, WindowGeometryFlags, WindowGeometryRequest, WindowState, WindowStyle,
+    },
+    EngineHandle, Window, WindowHandle,
+};
+
+// Allows controlling window from Rust; Operations go through the same code paths
+// as method calls from Dart. The proxy doesn't keep window alive; once window is
+// closed all operations fail with Error::InvalidWindowHandle.
+//
+// Operations must not be invoked while WindowManager is borrowed, as they may
+// synchronously call back into it (i.e. closing window removes it from manager).
+#[derive(Clone)]
+pub struct WindowProxy {
+    handle: WindowHandle,
+    engine: EngineHandle,
+    parent: Option<WindowHandle>,
+    window: Weak<Window>,
+}
+
+impl WindowProxy {
+    pub(super) fn new(window: &Rc<Window>) -> Self {
+        Self {
+            handle: window.window_handle,
+            engine: window.engine_handle,
+            parent: window.parent,
+            window: Rc::downgrade(window),
+        }
+    }
+
+    pub fn handle(&self) -> WindowHandle {
+        self.handle
+    }
+
+    pub fn engine(&self) -> EngineHandle {
+        self.engine
+    }
+
+    pub fn parent(&self) -> Option<WindowHandle> {
+        self.parent
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.window.strong_count() > 0
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.window.upgrade().is_some_and(|w| w.active.get())
+    }
+
+    fn window(&self) -> Result<Rc<Window>> {
+        self.window
+            .upgrade()
+            .ok_or(Error::InvalidWindowHandle(self.handle))
+    }
+
+    pub fn show(&self) -> Result<()> {
+        self.window()?.show()
+    }
+
+    pub fn hide(&self) -> Result<()> {
+        self.window()?.hide()
+    }
+
+    pub fn close(&self) -> Result<()> {
+        self.window()?.close()
+    }
+
+    pub fn set_geometry(&self, geometry: WindowGeometryRequest) -> Result<WindowGeometryFlags> {
+        self.window()?.set_geometry(geometry)
+    }
+
+    pub fn get_geometry(&self) -> Result<WindowGeometry> {
+        self.window()?.get_geometry()
+    }
+
+    pub fn set_style(&self, style: WindowStyle) -> Result<()> {
+        self.window()?.set_style(style)
+    }
+
+    pub fn set_title(&self, title: &str) -> Result<()> {
+        self.window()?.set_title(title)
+    }
+
+    pub fn set_state(&self, state: WindowState) -> Result<()> {
+        self.window()?.set_state(state)
+    }
+
+    pub fn get_state(&self) -> Result<WindowState> {
+        self.window()?.get_state()
+    }
+
+    pub fn activate(&self) -> Result<()> {
+        self.window()?.activate()
+    }
+
+    // Callback is invoked with result passed to close-with-result after the
+    // modal window is closed
+    pub fn show_modal<F>(&self, done_callback: F)
+    where
+        F: FnOnce(Result<Value>) + 'static,
+    {
+        match self.window() {
+            Ok(window) => window.show_modal(done_callback),
+            Err(error) => done_callback(Err(error)),
+        }
+    }
+}
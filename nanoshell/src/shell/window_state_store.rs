@@ -0,0 +1,328 @@
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::Duration,
+};
+
+use log::warn;
+
+use super::{structs::WindowState, Context, Rect};
+
+// Frame and state of a window, saved under window persistence key
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedWindowState {
+    // Frame of the window in normal state (not maximized or full screen)
+    pub frame: Rect,
+    // Always None on Windows (see PERSIST_DISPLAY)
+    pub display: Option<i64>,
+    pub state: WindowState,
+}
+
+enum Storage {
+    Disabled,
+    File(PathBuf),
+    #[cfg(test)]
+    Memory,
+}
+
+// Keeps geometry and state of windows created with persistence key in a JSON file
+// so that it can be restored next time the window is created; Disabled unless
+// ContextOptions::window_state_file is set.
+pub struct WindowStateStore {
+    context: Rc<Context>,
+    storage: Storage,
+    entries: HashMap<String, SavedWindowState>,
+    save_scheduled: Cell<bool>,
+}
+
+// Delay between state change and writing the file; Avoids writing on every frame
+// during live resize
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+// Display ids are HMONITOR handles on Windows, which are not stable across
+// sessions, so the display is not persisted there
+const PERSIST_DISPLAY: bool = !cfg!(target_os = "windows");
+
+impl WindowStateStore {
+    pub(super) fn new(context: Rc<Context>) -> Self {
+        let (storage, entries) = match context.options.window_state_file.clone() {
+            Some(path) => {
+                let entries = load(&path);
+                (Storage::File(path), entries)
+            }
+            None => (Storage::Disabled, HashMap::new()),
+        };
+        Self {
+            context,
+            storage,
+            entries,
+            save_scheduled: Cell::new(false),
+        }
+    }
+
+    // Store that keeps the state only for lifetime of the context
+    #[cfg(test)]
+    fn in_memory(context: Rc<Context>) -> Self {
+        Self {
+            context,
+            storage: Storage::Memory,
+            entries: HashMap::new(),
+            save_scheduled: Cell::new(false),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !matches!(self.storage, Storage::Disabled)
+    }
+
+    pub fn get(&self, key: &str) -> Option<SavedWindowState> {
+        self.entries.get(key).cloned()
+    }
+
+    pub fn set(&mut self, key: &str, state: SavedWindowState) {
+        let state = SavedWindowState {
+            display: state.display.filter(|_| PERSIST_DISPLAY),
+            ..state
+        };
+        if !self.is_enabled() || self.entries.get(key) == Some(&state) {
+            return;
+        }
+        self.entries.insert(key.into(), state);
+        if matches!(self.storage, Storage::File(_)) && !self.save_scheduled.replace(true) {
+            let context = Rc::downgrade(&self.context);
+            self.context
+                .run_loop
+                .borrow()
+                .schedule(
+                    move || {
+                        if let Some(context) = context.upgrade() {
+                            context.window_state_store.borrow().save();
+                        }
+                    },
+                    SAVE_DELAY,
+                )
+                .detach();
+        }
+    }
+
+    // Writes the file immediately
+    pub fn save(&self) {
+        self.save_scheduled.set(false);
+        if let Storage::File(path) = &self.storage {
+            if let Err(err) = write(path, &self.entries) {
+                warn!("Failed to save window state to {}: {}", path.display(), err);
+            }
+        }
+    }
+}
+
+fn load(path: &Path) -> HashMap<String, SavedWindowState> {
+    let mut entries: HashMap<String, SavedWindowState> = match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|err| {
+            warn!(
+                "Ignoring invalid window state file {}: {}",
+                path.display(),
+                err
+            );
+            HashMap::new()
+        }),
+        // Nothing saved yet
+        Err(_) => HashMap::new(),
+    };
+    if !PERSIST_DISPLAY {
+        for entry in entries.values_mut() {
+            entry.display = None;
+        }
+    }
+    entries
+}
+
+fn write(path: &Path, entries: &HashMap<String, SavedWindowState>) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(entries)?)
+}
+
+// window_state.json in per-user application configuration directory
+// (i.e. ~/Library/Application Support/<app_namespace> on macOS)
+pub fn default_window_state_file(app_namespace: &str) -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join(app_namespace).join("window_state.json"))
+}
+
+fn config_dir() -> Option<PathBuf> {
+    let home = || env::var_os("HOME").map(PathBuf::from);
+    if cfg!(target_os = "windows") {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home().map(|home| home.join(".config")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, env, fs, process, rc::Rc};
+
+    use crate::{
+        codec::Value,
+        shell::{
+            structs::{
+                Display, GeometryPreference, WindowCreateOptions, WindowGeometry,
+                WindowGeometryRequest, WindowState,
+            },
+            Context, Point, Rect, Size, WindowProxy,
+        },
+    };
+
+    use super::{load, write, SavedWindowState, WindowStateStore};
+
+    fn saved_state(state: WindowState) -> SavedWindowState {
+        SavedWindowState {
+            frame: Rect::xywh(10.0, 20.0, 800.0, 600.0),
+            display: Some(1),
+            state,
+        }
+    }
+
+    #[test]
+    fn test_file() {
+        let path = env::temp_dir()
+            .join(format!("nanoshell-{}", process::id()))
+            .join("window_state.json");
+        assert!(load(&path).is_empty());
+
+        let mut entries = HashMap::new();
+        entries.insert("main".to_string(), saved_state(WindowState::Maximized));
+        write(&path, &entries).unwrap();
+        assert_eq!(load(&path), entries);
+
+        // invalid content is ignored
+        fs::write(&path, "{\"main\": 1}").unwrap();
+        assert!(load(&path).is_empty());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_store() {
+        let context = Context::new(Default::default()).unwrap();
+        let mut store = WindowStateStore::new(context.clone());
+        assert!(!store.is_enabled());
+        store.set("main", saved_state(WindowState::Normal));
+        assert_eq!(store.get("main"), None);
+
+        let mut store = WindowStateStore::in_memory(context);
+        assert!(store.is_enabled());
+        store.set("main", saved_state(WindowState::Normal));
+        assert_eq!(store.get("main"), Some(saved_state(WindowState::Normal)));
+        store.set("main", saved_state(WindowState::FullScreen));
+        assert_eq!(
+            store.get("main"),
+            Some(saved_state(WindowState::FullScreen))
+        );
+    }
+
+    // Context with in-memory store and single 1600x1000 display
+    fn persistence_context() -> Rc<Context> {
+        let context = Context::new(Default::default()).unwrap();
+        *context.window_state_store.borrow_mut() = WindowStateStore::in_memory(context.clone());
+        context
+            .display_manager
+            .borrow()
+            .get_platform_display_manager()
+            .set_displays(vec![Display {
+                id: 1,
+                frame: Rect::xywh(0.0, 0.0, 1600.0, 1000.0),
+                work_area: Rect::xywh(0.0, 0.0, 1600.0, 1000.0),
+                scale: 1.0,
+            }]);
+        context
+    }
+
+    fn create_persistent_window(context: &Context) -> WindowProxy {
+        let mut window_manager = context.window_manager.borrow_mut();
+        let handle = window_manager.create_window_with_options(
+            Value::Null,
+            None,
+            WindowCreateOptions {
+                persistence_key: Some("main".into()),
+                ..Default::default()
+            },
+        );
+        window_manager.get_window_proxy(handle).unwrap()
+    }
+
+    #[test]
+    fn test_persistence() {
+        let context = persistence_context();
+        let proxy = create_persistent_window(&context);
+        proxy
+            .set_geometry(WindowGeometryRequest {
+                geometry: WindowGeometry {
+                    frame_origin: Some(Point::xy(200.0, 200.0)),
+                    frame_size: Some(Size::wh(800.0, 600.0)),
+                    ..Default::default()
+                },
+                preference: GeometryPreference::PreferFrame,
+                placement: None,
+            })
+            .unwrap();
+        proxy.set_state(WindowState::Maximized).unwrap();
+        assert_eq!(
+            context.window_state_store.borrow().get("main"),
+            Some(SavedWindowState {
+                frame: Rect::xywh(200.0, 200.0, 800.0, 600.0),
+                display: Some(1),
+                state: WindowState::Maximized,
+            })
+        );
+    }
+
+    #[test]
+    fn test_restore() {
+        let context = persistence_context();
+        context.window_state_store.borrow_mut().set(
+            "main",
+            SavedWindowState {
+                // partially off-screen
+                frame: Rect::xywh(1500.0, 200.0, 800.0, 600.0),
+                display: Some(1),
+                state: WindowState::Normal,
+            },
+        );
+        let proxy = create_persistent_window(&context);
+        let geometry = proxy.get_geometry().unwrap();
+        assert_eq!(geometry.frame_origin, Some(Point::xy(800.0, 200.0)));
+        assert_eq!(geometry.frame_size, Some(Size::wh(800.0, 600.0)));
+    }
+
+    #[test]
+    fn test_restore_maximized() {
+        let context = persistence_context();
+        let saved = SavedWindowState {
+            frame: Rect::xywh(100.0, 100.0, 800.0, 600.0),
+            display: Some(1),
+            state: WindowState::Maximized,
+        };
+        context
+            .window_state_store
+            .borrow_mut()
+            .set("main", saved.clone());
+        // state change must not be reported while window manager is borrowed
+        let proxy = create_persistent_window(&context);
+        assert_eq!(proxy.get_state().unwrap(), WindowState::Normal);
+
+        let run_loop = context.run_loop.borrow().clone();
+        run_loop.run();
+        assert_eq!(proxy.get_state().unwrap(), WindowState::Maximized);
+        // normal frame is kept
+        assert_eq!(context.window_state_store.borrow().get("main"), Some(saved));
+    }
+}
@@ -1,16 +1,41 @@
-use std::cell::{Ref, RefCell, RefMut};
+use std::{
+    cell::{Ref, RefCell, RefMut},
+    panic::Location,
+};
+
+#[cfg(debug_assertions)]
+use std::cell::Cell;
+
+use super::DispatchScope;
 
 // RefCell implementation that supports late initialization and can only be set once;
 // Panics if data is accessed before set has been called or if set is called more than once.
 #[derive(Clone)]
 pub struct LateRefCell<T> {
     value: RefCell<Option<T>>,
+    name: Option<&'static str>,
+
+    // Location of most recent mutable borrow, reported on reentrant borrow while
+    // that borrow is still live
+    #[cfg(debug_assertions)]
+    last_borrow_mut: Cell<Option<&'static Location<'static>>>,
 }
 
 impl<T> LateRefCell<T> {
     pub fn new() -> Self {
         Self {
             value: RefCell::new(None),
+            name: None,
+            #[cfg(debug_assertions)]
+            last_borrow_mut: Cell::new(None),
+        }
+    }
+
+    // Named cell reports its name when borrowed reentrantly
+    pub fn named(name: &'static str) -> Self {
+        Self {
+            name: Some(name),
+            ..Self::new()
         }
     }
 
@@ -41,11 +66,79 @@ impl<T> LateRefCell<T> {
         }
     }
 
+    #[track_caller]
     pub fn borrow(&self) -> Ref<T> {
-        Ref::map(self.value.borrow(), |t| t.as_ref().unwrap())
+        match self.value.try_borrow() {
+            Ok(value) => Ref::map(value, |t| t.as_ref().unwrap()),
+            Err(_) => self.reentrant_borrow(),
+        }
     }
 
+    #[track_caller]
     pub fn borrow_mut(&self) -> RefMut<T> {
-        RefMut::map(self.value.borrow_mut(), |t| t.as_mut().unwrap())
+        match self.value.try_borrow_mut() {
+            Ok(value) => {
+                #[cfg(debug_assertions)]
+                self.last_borrow_mut.set(Some(Location::caller()));
+                RefMut::map(value, |t| t.as_mut().unwrap())
+            }
+            Err(_) => self.reentrant_borrow(),
+        }
+    }
+
+    #[track_caller]
+    fn reentrant_borrow(&self) -> ! {
+        let mut message = format!(
+            "{} is already borrowed (reentrant borrow at {})",
+            self.name.unwrap_or("LateRefCell"),
+            Location::caller()
+        );
+        if let Some(channel) = DispatchScope::current_channel() {
+            message.push_str(&format!(
+                "; while dispatching message on channel {}",
+                channel
+            ));
+        }
+        // immutable borrow only fails while the value is mutably borrowed
+        #[cfg(debug_assertions)]
+        if let (Some(location), Err(_)) = (self.last_borrow_mut.get(), self.value.try_borrow()) {
+            message.push_str(&format!("; mutably borrowed at {}", location));
+        }
+        panic!("{}", message);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    use super::LateRefCell;
+
+    fn panic_message<F: FnOnce()>(f: F) -> String {
+        let error = catch_unwind(AssertUnwindSafe(f)).unwrap_err();
+        error.downcast_ref::<String>().cloned().unwrap_or_default()
+    }
+
+    #[test]
+    fn test_reentrant_borrow_message() {
+        let cell = LateRefCell::named("cell");
+        cell.set(1);
+
+        let message = panic_message(|| {
+            let _borrow = cell.borrow_mut();
+            cell.borrow();
+        });
+        assert!(message.starts_with("cell is already borrowed"));
+        #[cfg(debug_assertions)]
+        assert!(message.contains("; mutably borrowed at"));
+
+        // mutable borrow has ended, must not be reported
+        *cell.borrow_mut() = 2;
+        let message = panic_message(|| {
+            let _borrow = cell.borrow();
+            cell.borrow_mut();
+        });
+        assert!(message.starts_with("cell is already borrowed"));
+        assert!(!message.contains("mutably borrowed at"));
     }
 }
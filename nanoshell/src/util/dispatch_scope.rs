@@ -0,0 +1,35 @@
+#[cfg(debug_assertions)]
+thread_local! {
+    static CHANNELS: std::cell::RefCell<Vec<String>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+// Marks that a message on given channel is being dispatched; Used in debug builds
+// to report which channel handler caused reentrant borrow of a manager.
+pub struct DispatchScope {
+    _private: (),
+}
+
+impl DispatchScope {
+    pub fn enter(channel: &str) -> Self {
+        #[cfg(debug_assertions)]
+        CHANNELS.with(|c| c.borrow_mut().push(channel.into()));
+        #[cfg(not(debug_assertions))]
+        let _ = channel;
+        Self { _private: () }
+    }
+
+    // Innermost channel currently being dispatched (always None in release builds)
+    pub fn current_channel() -> Option<String> {
+        #[cfg(debug_assertions)]
+        return CHANNELS.with(|c| c.borrow().last().cloned());
+        #[cfg(not(debug_assertions))]
+        return None;
+    }
+}
+
+impl Drop for DispatchScope {
+    fn drop(&mut self) {
+        #[cfg(debug_assertions)]
+        CHANNELS.with(|c| c.borrow_mut().pop());
+    }
+}
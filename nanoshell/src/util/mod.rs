@@ -1,9 +1,12 @@
 mod capsule;
 mod cell;
 mod diff;
+mod dispatch_scope;
 mod log;
+pub(crate) mod trace;
 
 pub use self::diff::*;
+pub use self::dispatch_scope::*;
 pub use self::log::*;
 pub use capsule::*;
 pub use cell::*;
@@ -0,0 +1,68 @@
+// Spans for message handling; With "tracing" feature these are `tracing` spans,
+// otherwise they compile to nothing.
+
+#[cfg(feature = "tracing")]
+mod imp {
+    use std::time::Duration;
+
+    pub type Span = tracing::Span;
+
+    pub fn channel_handler_span(channel: &str) -> Span {
+        tracing::debug_span!("channel_handler", channel)
+    }
+
+    pub fn method_call_span(channel: &str, method: &str) -> Span {
+        tracing::debug_span!(
+            "method_call",
+            channel,
+            method,
+            latency_us = tracing::field::Empty
+        )
+    }
+
+    pub fn window_method_span(channel: &str, method: &str, target_window: i64) -> Span {
+        tracing::debug_span!("window_method_call", channel, method, target_window)
+    }
+
+    pub fn run_loop_callback_span() -> Span {
+        tracing::trace_span!("run_loop_callback")
+    }
+
+    pub fn record_latency(span: &Span, latency: Duration) {
+        span.record("latency_us", latency.as_micros() as u64);
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+mod imp {
+    use std::time::Duration;
+
+    #[derive(Clone)]
+    pub struct Span;
+
+    impl Span {
+        pub fn enter(&self) -> Span {
+            Span
+        }
+    }
+
+    pub fn channel_handler_span(_channel: &str) -> Span {
+        Span
+    }
+
+    pub fn method_call_span(_channel: &str, _method: &str) -> Span {
+        Span
+    }
+
+    pub fn window_method_span(_channel: &str, _method: &str, _target_window: i64) -> Span {
+        Span
+    }
+
+    pub fn run_loop_callback_span() -> Span {
+        Span
+    }
+
+    pub fn record_latency(_span: &Span, _latency: Duration) {}
+}
+
+pub(crate) use imp::*;
@@ -8,7 +8,7 @@ use std::{
 };
 
 use nanoshell::{
-    codec::{value::from_value, MethodCall, MethodCallReply, Value},
+    codec::{value::from_value, MethodCall, MethodCallReply, StandardMethodCodec, Value},
     shell::{Context, WindowHandle},
 };
 
@@ -70,7 +70,8 @@ impl FileOpenDialogService {
             .message_manager
             .borrow_mut()
             .register_method_handler(
-                "file_open_dialog_channel", //
+                "file_open_dialog_channel",
+                &StandardMethodCodec,
                 move |call, reply, _engine| {
                     if let Some(s) = weak_self.upgrade() {
                         s.on_method_call(call, reply);